@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::attack::AttackCategory;
+use super::damage::{Damage, DamageType};
+use super::event::FailReason;
+use super::stats::Stat;
+use super::status::{StatusEffect, StatusKind};
+use super::world::Combatant;
+
+/// Who an ability can be aimed at, relative to the user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Targeting {
+    User,
+    Enemy,
+    AllEnemies,
+    /// Anyone on the user's side, the user included.
+    Ally,
+}
+
+impl Targeting {
+    /// Whether `target` is a valid pick for `user`. `AllEnemies` ignores the pick.
+    pub fn allows(self, user: &Combatant, target: &Combatant) -> bool {
+        match self {
+            Targeting::User => user.id == target.id,
+            Targeting::Enemy => user.faction != target.faction,
+            Targeting::AllEnemies => true,
+            Targeting::Ally => user.faction == target.faction,
+        }
+    }
+}
+
+/// One damage type of an ability: `base` plus `percent` of the scaling stat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct DamageSpec {
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub base: i32,
+    #[serde(default)]
+    pub percent: i32,
+}
+
+/// HP an ability restores: `base` plus `percent` of the scaling stat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct HealSpec {
+    #[serde(default)]
+    pub base: i32,
+    #[serde(default)]
+    pub percent: i32,
+}
+
+/// Loaded from `abilities.ron`, see `content`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Ability {
+    pub name: String,
+    pub category: AttackCategory,
+    pub targeting: Targeting,
+    #[serde(default)]
+    pub mp_cost: i32,
+    #[serde(default)]
+    pub stamina_cost: i32,
+    /// Turns of the user before it can be used again, 0 for none.
+    #[serde(default)]
+    pub cooldown: u32,
+    pub scaling: Stat,
+    /// Empty for abilities that only apply statuses.
+    #[serde(default)]
+    pub damage: Vec<DamageSpec>,
+    #[serde(default)]
+    pub heal: Option<HealSpec>,
+    #[serde(default)]
+    pub on_hit: Vec<StatusEffect>,
+    #[serde(default)]
+    pub on_self: Vec<StatusEffect>,
+}
+
+impl Ability {
+    /// The HP `user` restores with this, None if it does not heal.
+    pub fn heal_for(&self, user: &Combatant) -> Option<i32> {
+        let stat = user.stat(self.scaling);
+        self.heal.map(|heal| (heal.base + stat * heal.percent / 100).max(0))
+    }
+
+    /// The damage `user` deals with this before any roll, None if it deals none.
+    pub fn damage_for(&self, user: &Combatant) -> Option<Damage> {
+        let stat = user.stat(self.scaling);
+        self.damage.iter()
+            .map(|spec| (spec.damage_type, (spec.base + stat * spec.percent / 100).max(0)))
+            .fold(None, |damage: Option<Damage>, (damage_type, amount)| Some(match damage {
+                Some(damage) => damage.and(damage_type, amount),
+                None => Damage::new(damage_type, amount),
+            }))
+    }
+}
+
+/// Turns of the owner left before each ability can be used again.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cooldowns {
+    turns: HashMap<String, u32>,
+    /// Started this turn, so this turn does not count.
+    fresh: Vec<String>,
+}
+
+impl Cooldowns {
+    pub fn remaining(&self, ability: &str) -> u32 {
+        self.turns.get(ability).copied().unwrap_or(0)
+    }
+
+    pub fn start(&mut self, ability: &Ability) {
+        if ability.cooldown > 0 {
+            self.turns.insert(ability.name.clone(), ability.cooldown);
+            self.fresh.push(ability.name.clone());
+        }
+    }
+
+    /// One turn of the owner has passed.
+    pub fn count_down(&mut self) {
+        let fresh = std::mem::take(&mut self.fresh);
+        for (_, turns) in self.turns.iter_mut().filter(|(ability, _)| !fresh.contains(ability)) {
+            *turns = turns.saturating_sub(1);
+        }
+        self.turns.retain(|_, turns| *turns > 0);
+    }
+}
+
+impl Combatant {
+    pub fn knows(&self, ability: &str) -> bool {
+        self.abilities.iter().any(|a| a == ability)
+    }
+
+    /// Why this combatant can't use `ability` right now, if anything stops them.
+    pub fn check_ability(&self, ability: &Ability) -> Result<(), FailReason> {
+        if !self.knows(&ability.name) {
+            Err(FailReason::UnknownAbility)
+        } else if ability.category == AttackCategory::Spell && self.statuses.has(StatusKind::Silence) {
+            Err(FailReason::Silenced)
+        } else if self.cooldowns.remaining(&ability.name) > 0 {
+            Err(FailReason::OnCooldown(self.cooldowns.remaining(&ability.name)))
+        } else if self.mp.current < ability.mp_cost {
+            Err(FailReason::NotEnoughMana)
+        } else if self.stamina.current < ability.stamina_cost {
+            Err(FailReason::NotEnoughStamina)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether `ability` could be aimed at `target` by this combatant.
+    pub fn can_target(&self, ability: &Ability, target: &Combatant) -> bool {
+        target.is_alive() && ability.targeting.allows(self, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ability(name: &str, cooldown: u32) -> Ability {
+        let source = format!("(name: {:?}, category: Spell, targeting: Enemy, cooldown: {}, scaling: Intelligence)", name, cooldown);
+        ron::de::from_str(&source).expect("valid ability")
+    }
+
+    #[test]
+    fn cooldown_is_not_counted_down_the_turn_it_starts() {
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.start(&ability("Fireball", 2));
+        cooldowns.count_down();
+        assert_eq!(cooldowns.remaining("Fireball"), 2);
+        cooldowns.count_down();
+        assert_eq!(cooldowns.remaining("Fireball"), 1);
+        cooldowns.count_down();
+        assert_eq!(cooldowns.remaining("Fireball"), 0);
+    }
+
+    #[test]
+    fn older_cooldowns_keep_counting_down() {
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.start(&ability("Fireball", 2));
+        cooldowns.count_down();
+        cooldowns.start(&ability("Mend", 1));
+        cooldowns.count_down();
+        assert_eq!(cooldowns.remaining("Fireball"), 1);
+        assert_eq!(cooldowns.remaining("Mend"), 1);
+        cooldowns.count_down();
+        assert_eq!(cooldowns.remaining("Mend"), 0);
+    }
+}
@@ -0,0 +1,291 @@
+use serde::Deserialize;
+
+use super::ability::{Ability, Targeting};
+use super::attack::AttackCategory;
+use super::combat::ActionKind;
+use super::stance::Stance;
+use super::world::{Combatant, CombatantId, World};
+
+/// The built in behaviors, picked per enemy template in `enemies.ron`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum AiProfile {
+    #[default]
+    Aggressive,
+    Caster,
+    Healer,
+    Coward,
+}
+
+impl AiProfile {
+    pub fn name(self) -> &'static str {
+        match self {
+            AiProfile::Aggressive => "Aggressive",
+            AiProfile::Caster => "Caster",
+            AiProfile::Healer => "Healer",
+            AiProfile::Coward => "Coward",
+        }
+    }
+
+    pub fn behavior(self) -> &'static dyn Behavior {
+        match self {
+            AiProfile::Aggressive => &Aggressive,
+            AiProfile::Caster => &Caster { mp_reserve: 30 },
+            AiProfile::Healer => &Healer { threshold: 50 },
+            AiProfile::Coward => &Coward { threshold: 30 },
+        }
+    }
+}
+
+/// Why an AI picked what it did, shown in the log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecisionReason {
+    StrongestAttack,
+    CastingSpell,
+    ConservingMana,
+    Supporting(CombatantId),
+    Healing(CombatantId),
+    Panicking,
+    NothingBetter,
+}
+
+pub struct Decision {
+    pub kind: ActionKind,
+    pub reason: DecisionReason,
+}
+
+pub trait Behavior {
+    /// What `me` does on its turn. Only asked for living combatants.
+    fn decide(&self, world: &World, me: &Combatant) -> Decision;
+}
+
+/// Hits whoever is weakest as hard as it can.
+pub struct Aggressive;
+
+/// Casts spells while it has more than `mp_reserve` percent MP left, then fights without them.
+pub struct Caster {
+    pub mp_reserve: i32,
+}
+
+/// Heals allies below `threshold` percent HP, otherwise fights.
+pub struct Healer {
+    pub threshold: i32,
+}
+
+/// Fights until below `threshold` percent HP, then turns defensive and tries to get away.
+pub struct Coward {
+    pub threshold: i32,
+}
+
+impl Behavior for Aggressive {
+    fn decide(&self, world: &World, me: &Combatant) -> Decision {
+        fight(world, me, |_| true, DecisionReason::StrongestAttack)
+    }
+}
+
+impl Behavior for Caster {
+    fn decide(&self, world: &World, me: &Combatant) -> Decision {
+        let is_spell = |a: &Ability| a.category == AttackCategory::Spell;
+        if percent(me.mp.fraction()) <= self.mp_reserve {
+            return fight(world, me, |a| !is_spell(a), DecisionReason::ConservingMana);
+        }
+        if let Some(kind) = strongest_attack(world, me, is_spell) {
+            return Decision { kind, reason: DecisionReason::CastingSpell };
+        }
+        if let Some((kind, ally)) = support(world, me) {
+            return Decision { kind, reason: DecisionReason::Supporting(ally) };
+        }
+        fight(world, me, |_| true, DecisionReason::StrongestAttack)
+    }
+}
+
+impl Behavior for Healer {
+    fn decide(&self, world: &World, me: &Combatant) -> Decision {
+        let hurt = allies(world, me)
+            .filter(|c| percent(c.hp.fraction()) < self.threshold)
+            .min_by_key(|c| percent(c.hp.fraction()));
+        let heal = usable(world, me).find(|a| a.heal.is_some() && a.targeting != Targeting::AllEnemies);
+        match (hurt, heal) {
+            (Some(hurt), Some(heal)) if me.can_target(heal, hurt) => Decision {
+                kind: ActionKind::Ability { name: heal.name.clone(), target: hurt.id },
+                reason: DecisionReason::Healing(hurt.id),
+            },
+            _ => fight(world, me, |_| true, DecisionReason::StrongestAttack),
+        }
+    }
+}
+
+impl Behavior for Coward {
+    fn decide(&self, world: &World, me: &Combatant) -> Decision {
+        if percent(me.hp.fraction()) >= self.threshold {
+            return fight(world, me, |_| true, DecisionReason::StrongestAttack);
+        }
+        // Anything that helps running away, like a haste, comes first.
+        let kind = match support(world, me) {
+            Some((kind, ally)) if ally == me.id => kind,
+            _ if me.stance != Stance::Defensive => ActionKind::ChangeStance { stance: Stance::Defensive },
+            _ => ActionKind::Flee,
+        };
+        Decision { kind, reason: DecisionReason::Panicking }
+    }
+}
+
+/// What `actor` does on its turn, according to its profile, with the profile.
+pub fn decide(world: &World, actor: CombatantId) -> (AiProfile, Decision) {
+    let profile = world.combatant(actor).and_then(|c| c.ai).unwrap_or_default();
+    let decision = match world.combatant(actor) {
+        Some(me) if me.is_alive() => profile.behavior().decide(world, me),
+        _ => Decision { kind: ActionKind::Wait, reason: DecisionReason::NothingBetter },
+    };
+    (profile, decision)
+}
+
+fn percent(fraction: f32) -> i32 {
+    (fraction * 100.0) as i32
+}
+
+/// Abilities `me` knows and can pay for right now.
+fn usable<'a>(world: &'a World, me: &'a Combatant) -> impl Iterator<Item = &'a Ability> + 'a {
+    me.abilities.iter()
+        .filter_map(move |name| world.ability(name))
+        .filter(move |a| me.check_ability(a).is_ok())
+}
+
+/// Living combatants on the side of `me`, `me` included.
+fn allies<'a>(world: &'a World, me: &'a Combatant) -> impl Iterator<Item = &'a Combatant> + 'a {
+    world.combatants().iter().filter(move |c| c.is_alive() && c.faction == me.faction)
+}
+
+fn weakest_opponent<'a>(world: &'a World, me: &Combatant) -> Option<&'a Combatant> {
+    world.combatants().iter()
+        .filter(|c| c.is_alive() && c.faction != me.faction)
+        .min_by_key(|c| c.hp.current)
+}
+
+/// Damage before any roll or mitigation, summed over everyone it would hit.
+fn expected_damage(world: &World, me: &Combatant, ability: &Ability) -> i32 {
+    let per_target: i32 = ability.damage_for(me).map(|d| d.parts.iter().map(|p| p.amount).sum()).unwrap_or(0);
+    let targets = match ability.targeting {
+        Targeting::AllEnemies => world.combatants().iter().filter(|c| c.is_alive() && c.faction != me.faction).count(),
+        _ => 1,
+    };
+    per_target * targets as i32
+}
+
+/// The hardest hitting usable ability that passes `allowed`, aimed at the weakest opponent.
+fn strongest_attack(world: &World, me: &Combatant, allowed: impl Fn(&Ability) -> bool) -> Option<ActionKind> {
+    let target = weakest_opponent(world, me)?;
+    usable(world, me)
+        .filter(|a| !a.damage.is_empty() && allowed(a))
+        .filter(|a| a.targeting == Targeting::AllEnemies || me.can_target(a, target))
+        .max_by_key(|a| expected_damage(world, me, a))
+        .map(|a| ActionKind::Ability { name: a.name.clone(), target: target.id })
+}
+
+/// The best attack passing `allowed`, or a basic attack, explained with `reason`.
+fn fight(world: &World, me: &Combatant, allowed: impl Fn(&Ability) -> bool, reason: DecisionReason) -> Decision {
+    if let Some(kind) = strongest_attack(world, me, allowed) {
+        return Decision { kind, reason };
+    }
+    match weakest_opponent(world, me) {
+        Some(target) => Decision {
+            kind: ActionKind::Attack { target: target.id },
+            reason: if reason == DecisionReason::StrongestAttack { DecisionReason::NothingBetter } else { reason },
+        },
+        None => Decision { kind: ActionKind::Wait, reason: DecisionReason::NothingBetter },
+    }
+}
+
+/// A usable status-only ability on an ally that does not have its status yet,
+/// others before `me`.
+fn support(world: &World, me: &Combatant) -> Option<(ActionKind, CombatantId)> {
+    usable(world, me)
+        .filter(|a| a.damage.is_empty() && a.heal.is_none())
+        .find_map(|a| {
+            let status = a.on_hit.first().or_else(|| a.on_self.first())?.kind;
+            let ally = allies(world, me)
+                .filter(|c| me.can_target(a, c) && !c.statuses.has(status))
+                .min_by_key(|c| c.id == me.id)?;
+            Some((ActionKind::Ability { name: a.name.clone(), target: ally.id }, ally.id))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::game::ability::Cooldowns;
+    use crate::game::combat::Phase;
+    use crate::game::content::{Content, NewCharacter, CONTENT_DIR};
+    use crate::game::event::CombatEvent;
+    use crate::game::explore::Mode;
+    use crate::game::gameloop::gameloop;
+
+    fn content() -> Content {
+        Content::load(Path::new(CONTENT_DIR)).expect("content loads")
+    }
+
+    /// A fight against `enemies`, returning the world and the enemies' ids in order.
+    fn fight(content: &Content, enemies: &[&str]) -> (World, Vec<CombatantId>) {
+        let hero = NewCharacter { name: "Erik".to_string(), class: "Rogue".to_string(), race: "Human".to_string() };
+        let mut world = World::new_game(1, content, &hero);
+        let ids = enemies.iter()
+            .map(|name| world.spawn(content.enemy(name).expect("enemy exists")))
+            .collect();
+        world.mode = Mode::Combat { encounter: 0 };
+        (world, ids)
+    }
+
+    /// Plays `actor`'s turn and returns the reason it gave for what it did.
+    fn take_turn(world: &mut World, actor: CombatantId) -> (AiProfile, DecisionReason) {
+        world.combat.phase = Phase::EnemyTurn(actor);
+        gameloop(world);
+        world.drain_events().into_iter()
+            .find_map(|e| match e {
+                CombatEvent::Decided { actor: a, profile, reason } if a == actor => Some((profile, reason)),
+                _ => None,
+            })
+            .expect("the enemy decided")
+    }
+
+    #[test]
+    fn healer_heals_an_ally_under_half_hp() {
+        let content = content();
+        let (mut world, ids) = fight(&content, &["Acolyte", "Scarecrow"]);
+        let (healer, hurt) = (ids[0], ids[1]);
+        let ally = world.combatant_mut(hurt).unwrap();
+        ally.hp.current = ally.hp.max / 3;
+        assert_eq!(take_turn(&mut world, healer), (AiProfile::Healer, DecisionReason::Healing(hurt)));
+    }
+
+    #[test]
+    fn healer_fights_while_everyone_is_fine() {
+        let content = content();
+        let (mut world, ids) = fight(&content, &["Acolyte", "Scarecrow"]);
+        let (_, reason) = take_turn(&mut world, ids[0]);
+        assert!(!matches!(reason, DecisionReason::Healing(_)));
+    }
+
+    #[test]
+    fn caster_conserves_mana_when_low() {
+        let content = content();
+        let (mut world, ids) = fight(&content, &["Scarecrow"]);
+        assert_eq!(take_turn(&mut world, ids[0]), (AiProfile::Caster, DecisionReason::CastingSpell));
+        let caster = world.combatant_mut(ids[0]).unwrap();
+        caster.mp.current = caster.mp.max / 5;
+        caster.cooldowns = Cooldowns::default();
+        assert_eq!(take_turn(&mut world, ids[0]), (AiProfile::Caster, DecisionReason::ConservingMana));
+    }
+
+    #[test]
+    fn coward_flees_at_low_hp() {
+        let content = content();
+        let (mut world, ids) = fight(&content, &["Rat"]);
+        let rat = world.combatant_mut(ids[0]).unwrap();
+        rat.hp.current = rat.hp.max / 5;
+        rat.stance = Stance::Defensive;
+        rat.abilities.clear();
+        assert_eq!(decide(&world, ids[0]).1.kind, ActionKind::Flee);
+        assert_eq!(take_turn(&mut world, ids[0]), (AiProfile::Coward, DecisionReason::Panicking));
+    }
+}
@@ -0,0 +1,195 @@
+use serde::Deserialize;
+
+use super::world::Attributes;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum AttackCategory {
+    Melee,
+    Ranged,
+    Spell,
+}
+
+impl AttackCategory {
+    pub const ALL: [AttackCategory; 3] = [AttackCategory::Melee, AttackCategory::Ranged, AttackCategory::Spell];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AttackCategory::Melee => "Melee",
+            AttackCategory::Ranged => "Ranged",
+            AttackCategory::Spell => "Spell",
+        }
+    }
+}
+
+/// Hit and crit chance in percent for one attack category.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Rating {
+    pub hit: i32,
+    pub crit: i32,
+}
+
+impl std::ops::Add for Rating {
+    type Output = Rating;
+
+    fn add(self, other: Rating) -> Rating {
+        Rating { hit: self.hit + other.hit, crit: self.crit + other.crit }
+    }
+}
+
+/// Combat ratings derived from attributes, as listed on the character sheet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Ratings {
+    pub melee: Rating,
+    pub ranged: Rating,
+    pub spell: Rating,
+    pub spellpower: i32,
+    /// Subtracted from melee and ranged hit chance against us.
+    pub evasion: i32,
+    /// Subtracted from spell hit chance against us.
+    pub willpower: i32,
+}
+
+impl Ratings {
+    pub fn derive(a: &Attributes) -> Self {
+        Ratings {
+            melee: Rating { hit: 80 + a.strength / 4 + a.dexterity / 4, crit: 5 + a.dexterity / 4 },
+            ranged: Rating { hit: 75 + a.dexterity / 2, crit: 5 + a.dexterity / 3 },
+            spell: Rating { hit: 80 + a.intelligence / 2, crit: 5 + a.intelligence / 4 },
+            spellpower: a.intelligence * 2 + a.wisdom / 2,
+            evasion: a.dexterity / 2,
+            willpower: a.wisdom / 2,
+        }
+    }
+
+    pub fn rating(&self, category: AttackCategory) -> Rating {
+        match category {
+            AttackCategory::Melee => self.melee,
+            AttackCategory::Ranged => self.ranged,
+            AttackCategory::Spell => self.spell,
+        }
+    }
+
+    pub fn defense(&self, category: AttackCategory) -> i32 {
+        match category {
+            AttackCategory::Melee | AttackCategory::Ranged => self.evasion,
+            AttackCategory::Spell => self.willpower,
+        }
+    }
+}
+
+impl std::ops::Add for Ratings {
+    type Output = Ratings;
+
+    fn add(self, other: Ratings) -> Ratings {
+        Ratings {
+            melee: self.melee + other.melee,
+            ranged: self.ranged + other.ranged,
+            spell: self.spell + other.spell,
+            spellpower: self.spellpower + other.spellpower,
+            evasion: self.evasion + other.evasion,
+            willpower: self.willpower + other.willpower,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttackOutcome {
+    Miss,
+    Hit,
+    Crit,
+}
+
+pub const MIN_HIT_CHANCE: i32 = 5;
+pub const MAX_HIT_CHANCE: i32 = 95;
+pub const MAX_CRIT_CHANCE: i32 = 50;
+/// Crits deal this many percent of normal damage.
+pub const CRIT_DAMAGE_PERCENT: i32 = 150;
+
+pub fn hit_chance(category: AttackCategory, attacker: &Ratings, defender: &Ratings) -> i32 {
+    (attacker.rating(category).hit - defender.defense(category)).clamp(MIN_HIT_CHANCE, MAX_HIT_CHANCE)
+}
+
+pub fn crit_chance(category: AttackCategory, attacker: &Ratings) -> i32 {
+    attacker.rating(category).crit.clamp(0, MAX_CRIT_CHANCE)
+}
+
+/// Rolls whether an attack lands. `d100` must return a number in 0..100 and
+/// is called once for the hit roll and, if it hits, once for the crit roll.
+pub fn roll_attack(
+    category: AttackCategory,
+    attacker: &Ratings,
+    defender: &Ratings,
+    mut d100: impl FnMut() -> i32,
+) -> AttackOutcome {
+    if d100() >= hit_chance(category, attacker, defender) {
+        AttackOutcome::Miss
+    } else if d100() < crit_chance(category, attacker) {
+        AttackOutcome::Crit
+    } else {
+        AttackOutcome::Hit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rng::Rng;
+
+    fn ratings() -> (Ratings, Ratings) {
+        let attacker = Attributes { strength: 8, dexterity: 8, ..Attributes::default() };
+        let defender = Attributes { dexterity: 10, ..Attributes::default() };
+        (Ratings::derive(&attacker), Ratings::derive(&defender))
+    }
+
+    /// A d100 that returns `rolls` in order.
+    fn scripted(rolls: &[i32]) -> impl FnMut() -> i32 + '_ {
+        let mut rolls = rolls.iter();
+        move || *rolls.next().expect("no more rolls")
+    }
+
+    #[test]
+    fn derived_chances() {
+        let (attacker, defender) = ratings();
+        assert_eq!(attacker.melee, Rating { hit: 84, crit: 7 });
+        assert_eq!(hit_chance(AttackCategory::Melee, &attacker, &defender), 79);
+        assert_eq!(crit_chance(AttackCategory::Melee, &attacker), 7);
+    }
+
+    #[test]
+    fn rolls_decide_the_outcome() {
+        let (attacker, defender) = ratings();
+        let roll = |rolls: &[i32]| roll_attack(AttackCategory::Melee, &attacker, &defender, scripted(rolls));
+        assert_eq!(roll(&[79]), AttackOutcome::Miss);
+        assert_eq!(roll(&[78, 7]), AttackOutcome::Hit);
+        assert_eq!(roll(&[0, 6]), AttackOutcome::Crit);
+    }
+
+    #[test]
+    fn chances_are_clamped() {
+        let (attacker, defender) = ratings();
+        let sure = attacker + Ratings { melee: Rating { hit: 500, crit: 500 }, ..Ratings::default() };
+        assert_eq!(hit_chance(AttackCategory::Melee, &sure, &defender), MAX_HIT_CHANCE);
+        assert_eq!(crit_chance(AttackCategory::Melee, &sure), MAX_CRIT_CHANCE);
+        let hopeless = Ratings { evasion: 500, ..defender };
+        assert_eq!(hit_chance(AttackCategory::Melee, &attacker, &hopeless), MIN_HIT_CHANCE);
+    }
+
+    #[test]
+    fn seeded_rolls_give_every_outcome() {
+        let (attacker, defender) = ratings();
+        let mut rng = Rng::new(3);
+        let outcomes: Vec<AttackOutcome> = (0..500)
+            .map(|_| roll_attack(AttackCategory::Melee, &attacker, &defender, || rng.d100()))
+            .collect();
+        let mut again = Rng::new(3);
+        let replayed: Vec<AttackOutcome> = (0..500)
+            .map(|_| roll_attack(AttackCategory::Melee, &attacker, &defender, || again.d100()))
+            .collect();
+        assert_eq!(outcomes, replayed);
+        for outcome in [AttackOutcome::Miss, AttackOutcome::Hit, AttackOutcome::Crit].iter() {
+            assert!(outcomes.contains(outcome), "{:?} never rolled", outcome);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+
+use super::event::FailReason;
+use super::resolve::check_action;
+use super::stance::Stance;
+use super::world::{CombatantId, World};
+
+/// Where the combat state machine currently is. See `gameloop::step` for
+/// the transitions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    StartOfTurn,
+    NextActor,
+    AwaitingInput(CombatantId),
+    EnemyTurn(CombatantId),
+    Resolving,
+    EndOfTurn,
+    Victory,
+    Defeat,
+    /// The player got away, the enemies are still there.
+    Escaped,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionKind {
+    Attack { target: CombatantId },
+    Ability { name: String, target: CombatantId },
+    /// Uses the item in an inventory slot of the actor.
+    UseItem { slot: usize, target: CombatantId },
+    /// Raises block and evasion until the actor's next turn.
+    Block,
+    ChangeStance { stance: Stance },
+    Flee,
+    Wait,
+}
+
+/// What the player asked for through the UI. It becomes an `Action` for
+/// whoever the combat is awaiting input from, see `World::submit_intent`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlayerIntent {
+    Attack { target: CombatantId },
+    UseAbility { name: String, target: CombatantId },
+    UseItem { slot: usize, target: CombatantId },
+    Block,
+    ChangeStance { stance: Stance },
+    Escape,
+    Wait,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Action {
+    pub actor: CombatantId,
+    pub kind: ActionKind,
+}
+
+pub struct Combat {
+    pub phase: Phase,
+    pub turn: u32,
+    pub(super) actors_left: VecDeque<CombatantId>,
+    pub(super) pending: Option<Action>,
+    /// Set when the player flees successfully, ends the fight after the action.
+    pub(super) escaped: bool,
+    submitted: VecDeque<Action>,
+}
+
+impl Combat {
+    pub fn new() -> Self {
+        Combat {
+            phase: Phase::StartOfTurn,
+            turn: 0,
+            actors_left: VecDeque::new(),
+            pending: None,
+            escaped: false,
+            submitted: VecDeque::new(),
+        }
+    }
+
+    /// The combatant whose input the state machine is blocked on, if any.
+    pub fn awaiting_input(&self) -> Option<CombatantId> {
+        match self.phase {
+            Phase::AwaitingInput(actor) => Some(actor),
+            _ => None,
+        }
+    }
+
+    /// Queues an action from outside the game, to be resolved on the next tick.
+    pub fn submit(&mut self, action: Action) {
+        self.submitted.push_back(action);
+    }
+
+    /// A player intent as an action for the combatant we are waiting on,
+    /// None if it is not the player's turn.
+    fn action_for(&self, intent: PlayerIntent) -> Option<Action> {
+        let actor = self.awaiting_input()?;
+        let kind = match intent {
+            PlayerIntent::Attack { target } => ActionKind::Attack { target },
+            PlayerIntent::UseAbility { name, target } => ActionKind::Ability { name, target },
+            PlayerIntent::UseItem { slot, target } => ActionKind::UseItem { slot, target },
+            PlayerIntent::Block => ActionKind::Block,
+            PlayerIntent::ChangeStance { stance } => ActionKind::ChangeStance { stance },
+            PlayerIntent::Escape => ActionKind::Flee,
+            PlayerIntent::Wait => ActionKind::Wait,
+        };
+        Some(Action { actor, kind })
+    }
+
+    /// Takes the first submitted action belonging to `actor`.
+    /// Actions for anyone else are left in the queue.
+    pub(super) fn take_submitted(&mut self, actor: CombatantId) -> Option<Action> {
+        let index = self.submitted.iter().position(|a| a.actor == actor)?;
+        self.submitted.remove(index)
+    }
+}
+
+impl World {
+    /// Queues a player intent as an action for the combatant combat is waiting on.
+    /// An intent that can't be done is dropped with the reason, the turn is not used up.
+    pub fn submit_intent(&mut self, intent: PlayerIntent) -> Result<(), FailReason> {
+        let action = self.combat.action_for(intent).ok_or(FailReason::NotYourTurn)?;
+        check_action(self, &action)?;
+        self.combat.submit(action);
+        Ok(())
+    }
+}
+
+impl Default for Combat {
+    fn default() -> Self {
+        Combat::new()
+    }
+}
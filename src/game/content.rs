@@ -0,0 +1,455 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::ability::Ability;
+use super::ai::AiProfile;
+use super::damage::Resistances;
+use super::equipment::Gear;
+use super::level::LevelCurve;
+use super::stance::Stance;
+use super::world::{Attributes, Combatant, Faction};
+
+/// Where the content files live, relative to the working directory.
+pub const CONTENT_DIR: &str = "assets";
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Class {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Offered on the character creation screen, the others are for enemies.
+    #[serde(default)]
+    pub playable: bool,
+    /// Level 1 attributes.
+    #[serde(default)]
+    pub attributes: Attributes,
+    /// Added to the attributes on every level up.
+    #[serde(default)]
+    pub growth: Attributes,
+    #[serde(default)]
+    pub block: i32,
+    #[serde(default)]
+    pub abilities: Vec<String>,
+    /// Abilities that can be picked when leveling up.
+    #[serde(default)]
+    pub ability_pool: Vec<String>,
+    /// Starting inventory, item name and count.
+    #[serde(default)]
+    pub items: Vec<(String, u32)>,
+    /// Gear worn from the start.
+    #[serde(default)]
+    pub equipment: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Race {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Offered on the character creation screen, the others are for enemies.
+    #[serde(default)]
+    pub playable: bool,
+    /// Added on top of the class attributes.
+    #[serde(default)]
+    pub attributes: Attributes,
+    /// Added on top of the class growth.
+    #[serde(default)]
+    pub growth: Attributes,
+    #[serde(default)]
+    pub resistances: Resistances,
+    /// Learnable on top of the class ability pool.
+    #[serde(default)]
+    pub ability_pool: Vec<String>,
+}
+
+/// The choices made on the character creation screen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewCharacter {
+    pub name: String,
+    pub class: String,
+    pub race: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct EnemyTemplate {
+    pub name: String,
+    pub class: String,
+    pub race: String,
+    /// Known on top of the class abilities.
+    #[serde(default)]
+    pub abilities: Vec<String>,
+    #[serde(default)]
+    pub ai: AiProfile,
+    /// The stance it starts a fight in.
+    #[serde(default)]
+    pub stance: Stance,
+    /// Shared by whoever defeats it.
+    #[serde(default)]
+    pub xp: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Item {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub weight: i32,
+    /// How many fit in one inventory slot, 1 for gear.
+    #[serde(default = "single")]
+    pub stack: u32,
+    /// The ability the item casts when used, items without one can't be used.
+    #[serde(default)]
+    pub ability: Option<String>,
+    /// Set for items that can be worn.
+    #[serde(default)]
+    pub gear: Option<Gear>,
+}
+
+fn single() -> u32 {
+    1
+}
+
+/// A problem with a content file, pointing at the line when we know it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentError {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ContentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file.display(), line, self.message),
+            None => write!(f, "{}: {}", self.file.display(), self.message),
+        }
+    }
+}
+
+/// One content file as read from disk, kept around to point errors at lines.
+struct Source {
+    file: PathBuf,
+    text: String,
+}
+
+impl Source {
+    fn error(&self, line: Option<usize>, message: String) -> ContentError {
+        ContentError { file: self.file.clone(), line, message }
+    }
+
+    /// The first line quoting `reference` at or after the line quoting `owner`.
+    fn line_of(&self, owner: &str, reference: &str) -> Option<usize> {
+        let quoted = |s: &str| format!("\"{}\"", s);
+        let (owner, reference) = (quoted(owner), quoted(reference));
+        let lines: Vec<&str> = self.text.lines().collect();
+        let start = lines.iter().position(|l| l.contains(&owner)).unwrap_or(0);
+        lines[start..].iter().position(|l| l.contains(&reference)).map(|i| start + i + 1)
+    }
+}
+
+/// Everything the game reads from `CONTENT_DIR`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Content {
+    pub abilities: Vec<Ability>,
+    pub classes: Vec<Class>,
+    pub races: Vec<Race>,
+    pub enemies: Vec<EnemyTemplate>,
+    pub items: Vec<Item>,
+    pub levels: LevelCurve,
+}
+
+impl Content {
+    /// Reads and validates every content file in `dir`.
+    /// Returns all the problems found, not just the first one.
+    pub fn load(dir: &Path) -> Result<Content, Vec<ContentError>> {
+        let mut errors = Vec::new();
+        let (abilities, ability_source) = read(dir, "abilities.ron", &mut errors);
+        let (classes, class_source) = read(dir, "classes.ron", &mut errors);
+        let (races, race_source) = read(dir, "races.ron", &mut errors);
+        let (enemies, enemy_source) = read(dir, "enemies.ron", &mut errors);
+        let (items, item_source) = read(dir, "items.ron", &mut errors);
+        let (levels, level_source) = read::<LevelCurve>(dir, "levels.ron", &mut errors);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let content = Content { abilities, classes, races, enemies, items, levels };
+
+        let ability_names = unique_names(&ability_source, content.abilities.iter().map(|a| &a.name), &mut errors);
+        let class_names = unique_names(&class_source, content.classes.iter().map(|c| &c.name), &mut errors);
+        let race_names = unique_names(&race_source, content.races.iter().map(|r| &r.name), &mut errors);
+        unique_names(&enemy_source, content.enemies.iter().map(|e| &e.name), &mut errors);
+        let item_names = unique_names(&item_source, content.items.iter().map(|i| &i.name), &mut errors);
+
+        let mut check = |source: &Source, owner: &str, kind: &str, known: &HashSet<&str>, reference: &str| {
+            if !known.contains(reference) {
+                let line = source.line_of(owner, reference);
+                errors.push(source.error(line, format!("{} refers to unknown {} \"{}\"", owner, kind, reference)));
+            }
+        };
+        for class in &content.classes {
+            for ability in class.abilities.iter().chain(&class.ability_pool) {
+                check(&class_source, &class.name, "ability", &ability_names, ability);
+            }
+            for (item, _) in &class.items {
+                check(&class_source, &class.name, "item", &item_names, item);
+            }
+            for item in &class.equipment {
+                check(&class_source, &class.name, "item", &item_names, item);
+            }
+        }
+        for race in &content.races {
+            for ability in &race.ability_pool {
+                check(&race_source, &race.name, "ability", &ability_names, ability);
+            }
+        }
+        for enemy in &content.enemies {
+            check(&enemy_source, &enemy.name, "class", &class_names, &enemy.class);
+            check(&enemy_source, &enemy.name, "race", &race_names, &enemy.race);
+            for ability in &enemy.abilities {
+                check(&enemy_source, &enemy.name, "ability", &ability_names, ability);
+            }
+        }
+        for item in &content.items {
+            if let Some(ability) = &item.ability {
+                check(&item_source, &item.name, "ability", &ability_names, ability);
+            }
+        }
+        for class in &content.classes {
+            for item in class.equipment.iter().filter(|i| content.item(i).is_some_and(|i| i.gear.is_none())) {
+                let line = class_source.line_of(&class.name, item);
+                errors.push(class_source.error(line, format!("{} wears \"{}\", which is not gear", class.name, item)));
+            }
+        }
+
+        if content.levels.xp.windows(2).any(|w| w[0] >= w[1]) {
+            errors.push(level_source.error(None, "xp has to go up with every level".to_string()));
+        }
+
+        if errors.is_empty() {
+            Ok(content)
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn class(&self, name: &str) -> Option<&Class> {
+        self.classes.iter().find(|c| c.name == name)
+    }
+
+    pub fn race(&self, name: &str) -> Option<&Race> {
+        self.races.iter().find(|r| r.name == name)
+    }
+
+    pub fn item(&self, name: &str) -> Option<&Item> {
+        self.items.iter().find(|i| i.name == name)
+    }
+
+    /// A level 1 combatant built from a class and a race, with the class starting gear and items.
+    pub fn character(&self, name: &str, class: &str, race: &str, faction: Faction) -> Option<Combatant> {
+        let (class, race) = (self.class(class)?, self.race(race)?);
+        let abilities: Vec<&str> = class.abilities.iter().map(|a| a.as_str()).collect();
+        let mut c = Combatant::new(name, &class.name, &race.name, faction, class.attributes + race.attributes)
+            .with_resistances(race.resistances)
+            .with_block(class.block)
+            .with_abilities(&abilities);
+        for ability in class.ability_pool.iter().chain(&race.ability_pool) {
+            if !c.ability_pool.contains(ability) {
+                c.ability_pool.push(ability.clone());
+            }
+        }
+        c.growth = class.growth + race.growth;
+        for item in class.equipment.iter().filter_map(|item| self.item(item)) {
+            let _ = c.wear(item.clone());
+        }
+        for (item, count) in &class.items {
+            // Whatever does not fit is left behind.
+            if let Some(item) = self.item(item) {
+                let _ = c.inventory.add(item, *count);
+            }
+        }
+        Some(c)
+    }
+
+    pub fn enemy(&self, name: &str) -> Option<Combatant> {
+        let template = self.enemies.iter().find(|e| e.name == name)?;
+        let mut c = self.character(&template.name, &template.class, &template.race, Faction::Enemy)?;
+        c.abilities.extend(template.abilities.iter().cloned());
+        c.ai = Some(template.ai);
+        c.stance = template.stance;
+        c.xp_value = template.xp;
+        Some(c)
+    }
+
+    /// Updates what `c` got from content, its known abilities, ability pool, growth,
+    /// resistances, block, AI, XP value and the definitions of its worn gear.
+    /// Pools, attributes, statuses and cooldowns are left as they are,
+    /// and so are abilities learned by leveling up.
+    pub fn refresh(&self, c: &mut Combatant) {
+        let fresh = if c.is_player() {
+            self.character(&c.name, &c.class, &c.race, c.faction)
+        } else {
+            self.enemy(&c.name)
+        };
+        if let Some(fresh) = fresh {
+            let learned: Vec<String> = c.abilities.iter()
+                .filter(|a| !fresh.abilities.contains(a) && fresh.ability_pool.contains(a))
+                .cloned()
+                .collect();
+            c.abilities = fresh.abilities;
+            c.abilities.extend(learned);
+            c.ability_pool = fresh.ability_pool;
+            c.growth = fresh.growth;
+            c.xp_value = fresh.xp_value;
+            c.resistances = fresh.resistances;
+            c.block = fresh.block;
+            c.ai = fresh.ai;
+            c.equipment.refresh(|name| self.item(name));
+            c.grow_pools();
+        }
+    }
+}
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Notices when files in the content directory are added, removed or modified.
+pub struct ContentWatcher {
+    dir: PathBuf,
+    modified: HashMap<PathBuf, SystemTime>,
+    last_poll: Instant,
+}
+
+impl ContentWatcher {
+    pub fn new(dir: &Path) -> Self {
+        ContentWatcher { dir: dir.to_path_buf(), modified: modification_times(dir), last_poll: Instant::now() }
+    }
+
+    /// Whether anything changed since the last call. Cheap to call every frame,
+    /// the directory is only looked at a couple of times per second.
+    pub fn poll(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+        let modified = modification_times(&self.dir);
+        let changed = modified != self.modified;
+        self.modified = modified;
+        changed
+    }
+}
+
+fn modification_times(dir: &Path) -> HashMap<PathBuf, SystemTime> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return HashMap::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+        .collect()
+}
+
+/// Reads one file of `dir`, usually holding a list. Problems go to `errors`
+/// and leave the result empty.
+fn read<T: DeserializeOwned + Default>(dir: &Path, name: &str, errors: &mut Vec<ContentError>) -> (T, Source) {
+    let file = dir.join(name);
+    let text = match std::fs::read_to_string(&file) {
+        Ok(text) => text,
+        Err(e) => {
+            errors.push(ContentError { file: file.clone(), line: None, message: e.to_string() });
+            String::new()
+        }
+    };
+    let source = Source { file, text };
+    if source.text.is_empty() {
+        return (T::default(), source);
+    }
+    match ron::de::from_str(&source.text) {
+        Ok(value) => (value, source),
+        Err(e) => {
+            errors.push(source.error(Some(e.position.line), e.code.to_string()));
+            (T::default(), source)
+        }
+    }
+}
+
+/// The set of `names`, reporting the ones defined twice.
+fn unique_names<'a>(
+    source: &Source,
+    names: impl Iterator<Item = &'a String>,
+    errors: &mut Vec<ContentError>,
+) -> HashSet<&'a str> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name.as_str()) {
+            let line = source.text.lines()
+                .enumerate()
+                .filter(|(_, l)| l.contains(&format!("\"{}\"", name)))
+                .nth(1)
+                .map(|(i, _)| i + 1);
+            errors.push(source.error(line, format!("\"{}\" is defined more than once", name)));
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Loads a copy of the content with `from` replaced by `to` in `file`,
+    /// returning the errors and the line `to` ended up on.
+    fn load_broken(file: &str, from: &str, to: &str) -> (Vec<ContentError>, usize) {
+        let tag: String = to.chars().filter(|c| c.is_alphanumeric()).collect();
+        let dir = std::env::temp_dir().join(format!("dungeon_fantasy_content_{}_{}", std::process::id(), tag));
+        std::fs::create_dir_all(&dir).unwrap();
+        for entry in std::fs::read_dir(CONTENT_DIR).unwrap() {
+            let path = entry.unwrap().path();
+            std::fs::copy(&path, dir.join(path.file_name().unwrap())).unwrap();
+        }
+        let text = std::fs::read_to_string(dir.join(file)).unwrap();
+        assert!(text.contains(from), "{} has no {}", file, from);
+        let text = text.replacen(from, to, 1);
+        let line = text.lines().position(|l| l.contains(to)).unwrap() + 1;
+        std::fs::write(dir.join(file), text).unwrap();
+        let errors = Content::load(&dir).expect_err("broken content loads");
+        std::fs::remove_dir_all(&dir).unwrap();
+        (errors, line)
+    }
+
+    fn assert_points_at(errors: &[ContentError], file: &str, line: usize, mentions: &str) {
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        let error = &errors[0];
+        assert!(error.file.ends_with(file), "{}", error);
+        assert_eq!(error.line, Some(line), "{}", error);
+        assert!(error.to_string().contains(mentions), "{}", error);
+    }
+
+    #[test]
+    fn the_real_content_loads() {
+        assert!(Content::load(Path::new(CONTENT_DIR)).is_ok());
+    }
+
+    #[test]
+    fn unknown_ability() {
+        let (errors, line) = load_broken("classes.ron", "\"Kidney Shot\"", "\"Kidney Punch\"");
+        assert_points_at(&errors, "classes.ron", line, "Rogue refers to unknown ability \"Kidney Punch\"");
+    }
+
+    #[test]
+    fn unknown_item() {
+        let (errors, line) = load_broken("classes.ron", "(\"Healing Potion\", 3)", "(\"Healing Tonic\", 3)");
+        assert_points_at(&errors, "classes.ron", line, "Rogue refers to unknown item \"Healing Tonic\"");
+    }
+
+    #[test]
+    fn unknown_status() {
+        let (errors, line) = load_broken("abilities.ron", "status: Burn", "status: Scorch");
+        assert_points_at(&errors, "abilities.ron", line, "Scorch");
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum DamageType {
+    Fire,
+    Frost,
+    Nature,
+    Holy,
+    Dark,
+    Blunt,
+    Slash,
+    Pierce,
+}
+
+impl DamageType {
+    pub const ALL: [DamageType; 8] = [
+        DamageType::Fire,
+        DamageType::Frost,
+        DamageType::Nature,
+        DamageType::Holy,
+        DamageType::Dark,
+        DamageType::Blunt,
+        DamageType::Slash,
+        DamageType::Pierce,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageType::Fire => "fire",
+            DamageType::Frost => "frost",
+            DamageType::Nature => "nature",
+            DamageType::Holy => "holy",
+            DamageType::Dark => "dark",
+            DamageType::Blunt => "blunt",
+            DamageType::Slash => "slash",
+            DamageType::Pierce => "pierce",
+        }
+    }
+
+    /// Physical damage can be blocked, the rest can only be resisted.
+    pub fn is_physical(self) -> bool {
+        matches!(self, DamageType::Blunt | DamageType::Slash | DamageType::Pierce)
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for DamageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Resistance in percent per damage type. Negative values are weaknesses.
+/// Content files write them as a map, like `{Fire: -50}`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "HashMap<DamageType, i32>")]
+pub struct Resistances([i32; 8]);
+
+impl Resistances {
+    pub const MAX: i32 = 75;
+    pub const MIN: i32 = -100;
+
+    pub fn get(&self, damage_type: DamageType) -> i32 {
+        self.0[damage_type.index()]
+    }
+
+    pub fn set(&mut self, damage_type: DamageType, percent: i32) {
+        self.0[damage_type.index()] = percent;
+    }
+
+    pub fn with(mut self, damage_type: DamageType, percent: i32) -> Self {
+        self.set(damage_type, percent);
+        self
+    }
+
+    /// The resistance actually applied, after clamping.
+    pub fn effective(&self, damage_type: DamageType) -> i32 {
+        self.get(damage_type).clamp(Self::MIN, Self::MAX)
+    }
+}
+
+impl From<HashMap<DamageType, i32>> for Resistances {
+    fn from(map: HashMap<DamageType, i32>) -> Self {
+        map.into_iter().fold(Resistances::default(), |r, (damage_type, percent)| r.with(damage_type, percent))
+    }
+}
+
+impl std::ops::Add for Resistances {
+    type Output = Resistances;
+
+    fn add(self, other: Resistances) -> Resistances {
+        let mut sum = self;
+        for (a, b) in sum.0.iter_mut().zip(other.0.iter()) {
+            *a += b;
+        }
+        sum
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DamagePart {
+    pub damage_type: DamageType,
+    pub amount: i32,
+}
+
+/// A hit before mitigation, made up of one or more typed parts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Damage {
+    pub parts: Vec<DamagePart>,
+}
+
+impl Damage {
+    pub fn new(damage_type: DamageType, amount: i32) -> Self {
+        Damage { parts: vec![DamagePart { damage_type, amount }] }
+    }
+
+    pub fn and(mut self, damage_type: DamageType, amount: i32) -> Self {
+        self.parts.push(DamagePart { damage_type, amount });
+        self
+    }
+
+    pub fn scaled(&self, percent: i32) -> Self {
+        let parts = self.parts.iter()
+            .map(|p| DamagePart { damage_type: p.damage_type, amount: p.amount * percent / 100 })
+            .collect();
+        Damage { parts }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartResult {
+    pub damage_type: DamageType,
+    pub raw: i32,
+    /// Negative when the defender is weak to the damage type.
+    pub resisted: i32,
+    pub blocked: i32,
+}
+
+impl PartResult {
+    pub fn taken(&self) -> i32 {
+        (self.raw - self.resisted - self.blocked).max(0)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DamageResult {
+    pub parts: Vec<PartResult>,
+}
+
+impl DamageResult {
+    pub fn raw(&self) -> i32 {
+        self.parts.iter().map(|p| p.raw).sum()
+    }
+
+    pub fn resisted(&self) -> i32 {
+        self.parts.iter().map(|p| p.resisted).sum()
+    }
+
+    pub fn blocked(&self) -> i32 {
+        self.parts.iter().map(|p| p.blocked).sum()
+    }
+
+    pub fn taken(&self) -> i32 {
+        self.parts.iter().map(|p| p.taken()).sum()
+    }
+}
+
+/// Applies resistances to every part, then soaks physical parts with up to
+/// `block` points in the order they were dealt.
+pub fn mitigate(damage: &Damage, resistances: &Resistances, block: i32) -> DamageResult {
+    let mut block_left = block.max(0);
+    let parts = damage.parts.iter().map(|part| {
+        let raw = part.amount.max(0);
+        let resisted = raw * resistances.effective(part.damage_type) / 100;
+        let blocked = if part.damage_type.is_physical() {
+            let blocked = block_left.min(raw - resisted).max(0);
+            block_left -= blocked;
+            blocked
+        } else {
+            0
+        };
+        PartResult { damage_type: part.damage_type, raw, resisted, blocked }
+    }).collect();
+    DamageResult { parts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_soaks_physical_parts_in_order() {
+        let damage = Damage::new(DamageType::Slash, 5).and(DamageType::Fire, 10).and(DamageType::Blunt, 10);
+        let result = mitigate(&damage, &Resistances::default(), 8);
+        let blocked: Vec<i32> = result.parts.iter().map(|p| p.blocked).collect();
+        assert_eq!(blocked, vec![5, 0, 3]);
+        assert_eq!(result.taken(), 17);
+    }
+
+    #[test]
+    fn resistance_reduces_before_block() {
+        let damage = Damage::new(DamageType::Pierce, 20);
+        let result = mitigate(&damage, &Resistances::default().with(DamageType::Pierce, 50), 4);
+        assert_eq!((result.resisted(), result.blocked(), result.taken()), (10, 4, 6));
+    }
+
+    #[test]
+    fn weakness_adds_damage() {
+        let damage = Damage::new(DamageType::Fire, 20);
+        let result = mitigate(&damage, &Resistances::default().with(DamageType::Fire, -50), 10);
+        assert_eq!(result.resisted(), -10);
+        assert_eq!(result.blocked(), 0);
+        assert_eq!(result.taken(), 30);
+    }
+
+    #[test]
+    fn resistance_is_clamped() {
+        let resistances = Resistances::default().with(DamageType::Holy, 200).with(DamageType::Dark, -300);
+        assert_eq!(resistances.effective(DamageType::Holy), Resistances::MAX);
+        assert_eq!(resistances.effective(DamageType::Dark), Resistances::MIN);
+        let damage = Damage::new(DamageType::Holy, 100).and(DamageType::Dark, 100);
+        let result = mitigate(&damage, &resistances, 0);
+        let taken: Vec<i32> = result.parts.iter().map(|p| p.taken()).collect();
+        assert_eq!(taken, vec![25, 200]);
+    }
+
+    #[test]
+    fn damage_never_goes_below_zero() {
+        let damage = Damage::new(DamageType::Blunt, 5).and(DamageType::Frost, -10);
+        let result = mitigate(&damage, &Resistances::default(), 50);
+        assert!(result.parts.iter().all(|p| p.taken() == 0));
+        assert_eq!(result.taken(), 0);
+        let overblocked = PartResult { damage_type: DamageType::Slash, raw: 5, resisted: 3, blocked: 4 };
+        assert_eq!(overblocked.taken(), 0);
+    }
+}
@@ -0,0 +1,354 @@
+use super::content::Content;
+use super::rng::Rng;
+
+pub const DUNGEON_WIDTH: i32 = 48;
+pub const DUNGEON_HEIGHT: i32 = 32;
+const ROOM_ATTEMPTS: usize = 40;
+const MAX_ROOMS: usize = 12;
+const ROOM_MIN: i32 = 4;
+const ROOM_MAX: i32 = 9;
+/// Percent of rooms, besides the first, with enemies waiting in them.
+const ENCOUNTER_CHANCE: i32 = 60;
+const MAX_ENCOUNTER_SIZE: i32 = 3;
+/// Percent of rooms with something lying around.
+const TREASURE_CHANCE: i32 = 40;
+/// Mixed into the seed of the layout, so it does not share its rolls with combat.
+const LAYOUT_SALT: u64 = 0x5DEE_CE66_D1CE_B0A7;
+
+/// The generator a layout is made with, its own stream for `seed`.
+fn layout_rng(seed: u64) -> Rng {
+    Rng::new(seed ^ LAYOUT_SALT)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Wall,
+    Floor,
+    Door,
+    StairsUp,
+    StairsDown,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Pos {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Pos {
+    pub fn new(x: i32, y: i32) -> Self {
+        Pos { x, y }
+    }
+}
+
+/// A rectangle of floor, walls not included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Room {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Room {
+    pub fn center(&self) -> Pos {
+        Pos::new(self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.x >= self.x && pos.x < self.x + self.width && pos.y >= self.y && pos.y < self.y + self.height
+    }
+
+    /// Whether the rooms overlap or touch, counting the walls around them.
+    fn touches(&self, other: &Room) -> bool {
+        self.x - 1 <= other.x + other.width
+            && other.x - 1 <= self.x + self.width
+            && self.y - 1 <= other.y + other.height
+            && other.y - 1 <= self.y + self.height
+    }
+}
+
+/// Enemies waiting somewhere, fought when the player gets there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Encounter {
+    pub pos: Pos,
+    /// Enemy template names.
+    pub enemies: Vec<String>,
+    pub cleared: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Treasure {
+    pub pos: Pos,
+    pub item: String,
+    pub count: u32,
+    pub taken: bool,
+}
+
+/// One level of the dungeon, the same for the same seed and content.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dungeon {
+    pub seed: u64,
+    pub width: i32,
+    pub height: i32,
+    tiles: Vec<Tile>,
+    pub rooms: Vec<Room>,
+    pub encounters: Vec<Encounter>,
+    pub treasures: Vec<Treasure>,
+    /// Where the player arrives, on the stairs up.
+    pub start: Pos,
+}
+
+impl Dungeon {
+    /// Rooms joined by corridors, with stairs, doors, encounters and treasure.
+    /// Uses its own generator, so combat rolls are not affected by the layout.
+    pub fn generate(seed: u64, content: &Content) -> Self {
+        let mut rng = layout_rng(seed);
+        let mut dungeon = Dungeon {
+            seed,
+            width: DUNGEON_WIDTH,
+            height: DUNGEON_HEIGHT,
+            tiles: vec![Tile::Wall; (DUNGEON_WIDTH * DUNGEON_HEIGHT) as usize],
+            ..Dungeon::default()
+        };
+        for _ in 0..ROOM_ATTEMPTS {
+            if dungeon.rooms.len() == MAX_ROOMS {
+                break;
+            }
+            let (width, height) = (rng.range(ROOM_MIN, ROOM_MAX + 1), rng.range(ROOM_MIN, ROOM_MAX + 1));
+            let room = Room {
+                x: rng.range(1, DUNGEON_WIDTH - width - 1),
+                y: rng.range(1, DUNGEON_HEIGHT - height - 1),
+                width,
+                height,
+            };
+            if dungeon.rooms.iter().any(|r| r.touches(&room)) {
+                continue;
+            }
+            dungeon.carve_room(&room);
+            if let Some(previous) = dungeon.rooms.last() {
+                let (from, to) = (previous.center(), room.center());
+                dungeon.carve_corridor(from, to, rng.range(0, 2) == 0);
+            }
+            dungeon.rooms.push(room);
+        }
+        dungeon.place_doors();
+
+        let (first, last) = match (dungeon.rooms.first(), dungeon.rooms.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return dungeon,
+        };
+        dungeon.start = first.center();
+        dungeon.set(first.center(), Tile::StairsUp);
+        dungeon.set(last.center(), Tile::StairsDown);
+        dungeon.place_contents(&mut rng, content);
+        dungeon
+    }
+
+    pub fn tile(&self, pos: Pos) -> Tile {
+        if self.in_bounds(pos) {
+            self.tiles[(pos.y * self.width + pos.x) as usize]
+        } else {
+            Tile::Wall
+        }
+    }
+
+    pub fn room_at(&self, pos: Pos) -> Option<usize> {
+        self.rooms.iter().position(|r| r.contains(pos))
+    }
+
+    /// Whether nothing but open tiles lie between `from` and `to`, going diagonally first.
+    /// A diagonal step only gets past a corner when one of the two tiles beside it is open.
+    pub fn in_sight(&self, from: Pos, to: Pos) -> bool {
+        let open = |pos: Pos| self.tile(pos) != Tile::Wall;
+        let mut pos = from;
+        while pos != to {
+            let next = Pos::new(pos.x + (to.x - pos.x).signum(), pos.y + (to.y - pos.y).signum());
+            let corner = next.x != pos.x && next.y != pos.y;
+            if !open(next) || (corner && !open(Pos::new(next.x, pos.y)) && !open(Pos::new(pos.x, next.y))) {
+                return false;
+            }
+            pos = next;
+        }
+        true
+    }
+
+    fn in_bounds(&self, pos: Pos) -> bool {
+        pos.x >= 0 && pos.y >= 0 && pos.x < self.width && pos.y < self.height
+    }
+
+    fn set(&mut self, pos: Pos, tile: Tile) {
+        if self.in_bounds(pos) {
+            let index = (pos.y * self.width + pos.x) as usize;
+            self.tiles[index] = tile;
+        }
+    }
+
+    fn carve_room(&mut self, room: &Room) {
+        for y in room.y..room.y + room.height {
+            for x in room.x..room.x + room.width {
+                self.set(Pos::new(x, y), Tile::Floor);
+            }
+        }
+    }
+
+    /// An L shaped corridor, going sideways or up and down first.
+    fn carve_corridor(&mut self, from: Pos, to: Pos, sideways_first: bool) {
+        let corner = if sideways_first { Pos::new(to.x, from.y) } else { Pos::new(from.x, to.y) };
+        for (a, b) in [(from, corner), (corner, to)].iter() {
+            for x in a.x.min(b.x)..=a.x.max(b.x) {
+                for y in a.y.min(b.y)..=a.y.max(b.y) {
+                    self.set(Pos::new(x, y), Tile::Floor);
+                }
+            }
+        }
+    }
+
+    /// Doors go where a corridor passes through the wall around a room,
+    /// between two pieces of wall.
+    fn place_doors(&mut self) {
+        let mut doors = Vec::new();
+        for room in &self.rooms {
+            let ring = (room.x - 1..=room.x + room.width)
+                .flat_map(|x| vec![Pos::new(x, room.y - 1), Pos::new(x, room.y + room.height)])
+                .chain((room.y..room.y + room.height)
+                    .flat_map(|y| vec![Pos::new(room.x - 1, y), Pos::new(room.x + room.width, y)]));
+            for pos in ring.filter(|p| self.tile(*p) == Tile::Floor && self.room_at(*p).is_none()) {
+                let wall = |dx, dy| self.tile(Pos::new(pos.x + dx, pos.y + dy)) == Tile::Wall;
+                if (wall(-1, 0) && wall(1, 0)) || (wall(0, -1) && wall(0, 1)) {
+                    doors.push(pos);
+                }
+            }
+        }
+        for pos in doors {
+            self.set(pos, Tile::Door);
+        }
+    }
+
+    /// Encounters and treasure on free floor of the rooms past the first.
+    fn place_contents(&mut self, rng: &mut Rng, content: &Content) {
+        let rooms: Vec<Room> = self.rooms.iter().skip(1).copied().collect();
+        for room in rooms {
+            if !content.enemies.is_empty() && rng.d100() < ENCOUNTER_CHANCE {
+                let size = rng.range(1, MAX_ENCOUNTER_SIZE + 1);
+                let enemies = (0..size)
+                    .map(|_| content.enemies[rng.range(0, content.enemies.len() as i32) as usize].name.clone())
+                    .collect();
+                if let Some(pos) = self.free_spot(rng, &room) {
+                    self.encounters.push(Encounter { pos, enemies, cleared: false });
+                }
+            }
+            if !content.items.is_empty() && rng.d100() < TREASURE_CHANCE {
+                let item = &content.items[rng.range(0, content.items.len() as i32) as usize];
+                let count = rng.range(1, item.stack.clamp(1, 3) as i32 + 1) as u32;
+                if let Some(pos) = self.free_spot(rng, &room) {
+                    self.treasures.push(Treasure { pos, item: item.name.clone(), count, taken: false });
+                }
+            }
+        }
+    }
+
+    /// A floor tile in `room` with nothing on it yet, None if a few tries find none.
+    fn free_spot(&self, rng: &mut Rng, room: &Room) -> Option<Pos> {
+        (0..10)
+            .map(|_| Pos::new(rng.range(room.x, room.x + room.width), rng.range(room.y, room.y + room.height)))
+            .find(|pos| {
+                self.tile(*pos) == Tile::Floor
+                    && !self.encounters.iter().any(|e| e.pos == *pos)
+                    && !self.treasures.iter().any(|t| t.pos == *pos)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashSet, VecDeque};
+    use std::path::Path;
+
+    use super::*;
+    use crate::game::content::CONTENT_DIR;
+    use crate::game::world::World;
+
+    fn content() -> Content {
+        Content::load(Path::new(CONTENT_DIR)).expect("content loads")
+    }
+
+    /// Every tile that can be walked to from `start`.
+    fn reachable(dungeon: &Dungeon) -> HashSet<Pos> {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert(dungeon.start);
+        queue.push_back(dungeon.start);
+        while let Some(pos) = queue.pop_front() {
+            for (dx, dy) in [(0, -1), (0, 1), (1, 0), (-1, 0)].iter() {
+                let next = Pos::new(pos.x + dx, pos.y + dy);
+                if dungeon.tile(next) != Tile::Wall && seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    /// A 6x3 block of wall with `open` tiles carved out.
+    fn carved(open: &[(i32, i32)]) -> Dungeon {
+        let mut dungeon = Dungeon { width: 6, height: 3, tiles: vec![Tile::Wall; 18], ..Dungeon::default() };
+        for (x, y) in open {
+            dungeon.set(Pos::new(*x, *y), Tile::Floor);
+        }
+        dungeon
+    }
+
+    #[test]
+    fn no_sight_through_walls() {
+        // Diagonal neighbors with wall on both sides of the corner.
+        let corner = carved(&[(1, 1), (2, 2)]);
+        assert!(!corner.in_sight(Pos::new(1, 1), Pos::new(2, 2)));
+        let around = carved(&[(1, 1), (2, 1), (2, 2)]);
+        assert!(around.in_sight(Pos::new(1, 1), Pos::new(2, 2)));
+        assert!(around.in_sight(Pos::new(2, 2), Pos::new(1, 1)));
+        // A wall in the middle of a straight line.
+        let split = carved(&[(1, 1), (3, 1), (4, 1)]);
+        assert!(!split.in_sight(Pos::new(1, 1), Pos::new(3, 1)));
+        assert!(split.in_sight(Pos::new(3, 1), Pos::new(4, 1)));
+    }
+
+    #[test]
+    fn same_seed_same_dungeon() {
+        let content = content();
+        for seed in 0..20 {
+            assert_eq!(Dungeon::generate(seed, &content), Dungeon::generate(seed, &content));
+        }
+    }
+
+    #[test]
+    fn layout_does_not_share_rolls_with_combat() {
+        for seed in 0..20 {
+            let mut combat = World::new(seed).rng;
+            let mut layout = layout_rng(seed);
+            let combat: Vec<u64> = (0..64).map(|_| combat.next_u64()).collect();
+            let layout: Vec<u64> = (0..64).map(|_| layout.next_u64()).collect();
+            assert!(combat.iter().all(|roll| !layout.contains(roll)), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn every_room_is_reachable() {
+        let content = content();
+        for seed in 0..20 {
+            let dungeon = Dungeon::generate(seed, &content);
+            assert!(!dungeon.rooms.is_empty());
+            assert_eq!(dungeon.tile(dungeon.start), Tile::StairsUp);
+            let reachable = reachable(&dungeon);
+            for room in &dungeon.rooms {
+                assert!(reachable.contains(&room.center()), "seed {}: room {:?} cut off", seed, room);
+            }
+            for encounter in &dungeon.encounters {
+                assert!(reachable.contains(&encounter.pos), "seed {}: encounter cut off", seed);
+            }
+            for treasure in &dungeon.treasures {
+                assert!(reachable.contains(&treasure.pos), "seed {}: treasure cut off", seed);
+            }
+        }
+    }
+}
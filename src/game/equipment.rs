@@ -0,0 +1,223 @@
+use serde::Deserialize;
+
+use super::content::Item;
+use super::damage::DamageType;
+use super::inventory::InventoryError;
+use super::stats::Modifiers;
+use super::world::Combatant;
+
+/// Where a piece of gear goes, as written in `items.ron`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum GearSlot {
+    Weapon,
+    Offhand,
+    Head,
+    Chest,
+    Legs,
+    Trinket,
+}
+
+/// The slots of a combatant, trinkets fit in either of two.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EquipSlot {
+    Weapon,
+    Offhand,
+    Head,
+    Chest,
+    Legs,
+    Trinket1,
+    Trinket2,
+}
+
+impl EquipSlot {
+    pub const ALL: [EquipSlot; 7] = [
+        EquipSlot::Weapon,
+        EquipSlot::Offhand,
+        EquipSlot::Head,
+        EquipSlot::Chest,
+        EquipSlot::Legs,
+        EquipSlot::Trinket1,
+        EquipSlot::Trinket2,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EquipSlot::Weapon => "Weapon",
+            EquipSlot::Offhand => "Offhand",
+            EquipSlot::Head => "Head",
+            EquipSlot::Chest => "Chest",
+            EquipSlot::Legs => "Legs",
+            EquipSlot::Trinket1 | EquipSlot::Trinket2 => "Trinket",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The slots a piece of gear can go in, in order of preference.
+    fn fitting(slot: GearSlot) -> &'static [EquipSlot] {
+        match slot {
+            GearSlot::Weapon => &[EquipSlot::Weapon],
+            GearSlot::Offhand => &[EquipSlot::Offhand],
+            GearSlot::Head => &[EquipSlot::Head],
+            GearSlot::Chest => &[EquipSlot::Chest],
+            GearSlot::Legs => &[EquipSlot::Legs],
+            GearSlot::Trinket => &[EquipSlot::Trinket1, EquipSlot::Trinket2],
+        }
+    }
+}
+
+/// What makes an item wearable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct Gear {
+    pub slot: GearSlot,
+    #[serde(default)]
+    pub modifiers: Modifiers,
+    /// For weapons, the damage type of a basic attack.
+    #[serde(default)]
+    pub damage_type: Option<DamageType>,
+}
+
+/// Damage type of a basic attack without a weapon.
+pub const UNARMED_DAMAGE: DamageType = DamageType::Blunt;
+
+/// The worn items of a combatant, kept as a copy of their definition.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Equipment {
+    items: [Option<Item>; 7],
+}
+
+impl Equipment {
+    pub fn get(&self, slot: EquipSlot) -> Option<&Item> {
+        self.items[slot.index()].as_ref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Item> {
+        self.items.iter().flatten()
+    }
+
+    /// Everything the worn gear adds up to.
+    pub fn modifiers(&self) -> Modifiers {
+        self.iter()
+            .filter_map(|item| item.gear)
+            .fold(Modifiers::default(), |sum, gear| sum + gear.modifiers)
+    }
+
+    pub fn attack_damage_type(&self) -> DamageType {
+        self.get(EquipSlot::Weapon)
+            .and_then(|item| item.gear)
+            .and_then(|gear| gear.damage_type)
+            .unwrap_or(UNARMED_DAMAGE)
+    }
+
+    /// Puts `item` in the first free slot that fits it, or the first one that fits.
+    /// Returns what was worn there before.
+    fn put(&mut self, item: Item) -> Result<Option<Item>, InventoryError> {
+        let fitting = match item.gear {
+            Some(gear) => EquipSlot::fitting(gear.slot),
+            None => return Err(InventoryError::NotGear),
+        };
+        let slot = fitting.iter()
+            .find(|slot| self.get(**slot).is_none())
+            .unwrap_or(&fitting[0]);
+        Ok(self.items[slot.index()].replace(item))
+    }
+
+    /// Swaps in the current definitions, after content was reloaded.
+    pub fn refresh<'a>(&mut self, lookup: impl Fn(&str) -> Option<&'a Item>) {
+        for worn in self.items.iter_mut().flatten() {
+            if let Some(item) = lookup(&worn.name).filter(|item| item.gear.is_some()) {
+                *worn = item.clone();
+            }
+        }
+    }
+}
+
+impl Combatant {
+    /// Puts `item` on, without it passing through the inventory.
+    /// Whatever it replaces goes to the inventory.
+    pub fn wear(&mut self, item: Item) -> Result<(), InventoryError> {
+        let replaced = self.equipment.put(item)?;
+        self.grow_pools();
+        match replaced {
+            Some(replaced) => self.inventory.add(&replaced, 1),
+            None => Ok(()),
+        }
+    }
+
+    /// Wears the item in inventory `slot`, `item` being its definition.
+    pub fn equip(&mut self, slot: usize, item: &Item) -> Result<(), InventoryError> {
+        if item.gear.is_none() {
+            return Err(InventoryError::NotGear);
+        }
+        if self.inventory.take_one(slot).is_none() {
+            return Ok(());
+        }
+        let before = self.equipment.clone();
+        match self.wear(item.clone()) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                // The replaced item did not fit, put everything back.
+                self.equipment = before;
+                self.grow_pools();
+                let _ = self.inventory.add(item, 1);
+                Err(e)
+            }
+        }
+    }
+
+    /// Takes off what is worn in `slot` and puts it in the inventory, if there is room.
+    pub fn unequip(&mut self, slot: EquipSlot) -> Result<(), InventoryError> {
+        let item = match self.equipment.get(slot) {
+            Some(item) => item.clone(),
+            None => return Ok(()),
+        };
+        self.inventory.add(&item, 1)?;
+        self.equipment.items[slot.index()] = None;
+        self.grow_pools();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::world::{Attributes, Faction};
+
+    fn iron_cap() -> Item {
+        ron::de::from_str("(name: \"Iron Cap\", weight: 4, gear: Some((slot: Head, modifiers: (attributes: (constitution: 1)))))")
+            .expect("valid item")
+    }
+
+    fn hero() -> Combatant {
+        let attributes = Attributes { strength: 5, constitution: 5, ..Attributes::default() };
+        Combatant::new("Erik", "Rogue", "Human", Faction::Player, attributes)
+    }
+
+    #[test]
+    fn gear_attributes_change_the_pools() {
+        let mut c = hero();
+        let (hp, stamina) = (c.hp.max, c.stamina.max);
+        c.inventory.add(&iron_cap(), 1).unwrap();
+        c.equip(0, &iron_cap()).unwrap();
+        assert_eq!(c.hp.max, hp + 10);
+        assert_eq!(c.hp.current, hp + 10);
+        assert_eq!(c.stamina.max, stamina + 2);
+        c.unequip(EquipSlot::Head).unwrap();
+        assert_eq!(c.hp.max, hp);
+        assert_eq!(c.hp.current, hp);
+    }
+
+    #[test]
+    fn taking_gear_off_does_not_heal_or_kill() {
+        let mut c = hero();
+        c.inventory.add(&iron_cap(), 1).unwrap();
+        c.equip(0, &iron_cap()).unwrap();
+        c.hp.drain(c.hp.max - 5);
+        c.unequip(EquipSlot::Head).unwrap();
+        assert_eq!(c.hp.current, 1);
+        c.equip(0, &iron_cap()).unwrap();
+        assert_eq!(c.hp.current, 11);
+    }
+}
@@ -0,0 +1,64 @@
+use super::ai::{AiProfile, DecisionReason};
+use super::damage::DamageResult;
+use super::stance::Stance;
+use super::status::StatusKind;
+use super::world::CombatantId;
+
+/// Why a damage event happened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DamageCause {
+    Ability(String),
+    Status(StatusKind),
+}
+
+/// Why an action did nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailReason {
+    UnknownAbility,
+    Silenced,
+    /// Turns left before it can be used again.
+    OnCooldown(u32),
+    NotEnoughMana,
+    NotEnoughStamina,
+    InvalidTarget,
+    /// The item is gone or does nothing when used.
+    NotUsable,
+    /// Only asked for outside the player's turn.
+    NotYourTurn,
+}
+
+/// Everything that happens in combat, in the order it happens.
+/// The UI turns these into log lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CombatEvent {
+    /// The player ran into enemies while exploring, a fight starts.
+    Encountered { enemies: Vec<CombatantId> },
+    /// `taken` is false when it did not fit in the inventory and was left there.
+    FoundItem { target: CombatantId, item: String, count: u32, taken: bool },
+    TurnStarted { turn: u32 },
+    /// An AI made up its mind, just before the action it picked.
+    Decided { actor: CombatantId, profile: AiProfile, reason: DecisionReason },
+    AbilityUsed { actor: CombatantId, ability: String, target: Option<CombatantId> },
+    ActionFailed { actor: CombatantId, ability: String, reason: FailReason },
+    ItemUsed { actor: CombatantId, item: String, target: Option<CombatantId> },
+    Missed { actor: CombatantId, target: CombatantId, ability: String },
+    Damaged { source: Option<CombatantId>, target: CombatantId, cause: DamageCause, crit: bool, result: DamageResult },
+    Healed { source: CombatantId, target: CombatantId, ability: String, amount: i32 },
+    StatusApplied { target: CombatantId, status: StatusKind, stacks: u32 },
+    StatusExpired { target: CombatantId, status: StatusKind },
+    TurnLost { actor: CombatantId, status: StatusKind },
+    Waited { actor: CombatantId },
+    Guarded { actor: CombatantId },
+    StanceChanged { actor: CombatantId, stance: Stance },
+    /// `chance` is the percent chance the attempt had.
+    FleeFailed { actor: CombatantId, chance: i32 },
+    /// Out of the fight. For the player, the fight is over.
+    Fled { actor: CombatantId, chance: i32 },
+    Died { target: CombatantId },
+    XpGained { target: CombatantId, amount: u32 },
+    LeveledUp { target: CombatantId, level: u32 },
+    Victory,
+    Defeat,
+    /// The player got away, the fight is over.
+    Escaped,
+}
@@ -0,0 +1,119 @@
+use super::combat::{Combat, Phase};
+use super::content::Content;
+use super::dungeon::{Pos, Tile};
+use super::event::CombatEvent;
+use super::world::{CombatantId, World};
+
+/// What the game is doing. `gameloop` only runs combat while fighting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Exploring,
+    /// Fighting the encounter with this index in the dungeon.
+    Combat { encounter: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    pub fn step(self, pos: Pos) -> Pos {
+        match self {
+            Direction::North => Pos::new(pos.x, pos.y - 1),
+            Direction::South => Pos::new(pos.x, pos.y + 1),
+            Direction::East => Pos::new(pos.x + 1, pos.y),
+            Direction::West => Pos::new(pos.x - 1, pos.y),
+        }
+    }
+}
+
+impl World {
+    /// Moves the player one tile, picking up treasure and running into encounters.
+    /// Does nothing outside of exploration or into a wall.
+    pub fn move_player(&mut self, direction: Direction, content: &Content) {
+        let to = direction.step(self.position);
+        if self.mode != Mode::Exploring || self.dungeon.tile(to) == Tile::Wall {
+            return;
+        }
+        self.previous_position = self.position;
+        self.position = to;
+        self.pick_up_treasure();
+        // Enemies notice the player from the next tile over, unless there is a wall in the way.
+        let near = |pos: Pos| (pos.x - to.x).abs() <= 1 && (pos.y - to.y).abs() <= 1 && self.dungeon.in_sight(to, pos);
+        if let Some(index) = self.dungeon.encounters.iter().position(|e| !e.cleared && near(e.pos)) {
+            self.start_encounter(index, content);
+        }
+    }
+
+    fn pick_up_treasure(&mut self) {
+        let position = self.position;
+        let index = match self.dungeon.treasures.iter().position(|t| !t.taken && t.pos == position) {
+            Some(index) => index,
+            None => return,
+        };
+        let treasure = self.dungeon.treasures[index].clone();
+        let item = match self.item(&treasure.item) {
+            Some(item) => item.clone(),
+            None => return,
+        };
+        let player = match self.player_mut() {
+            Some(player) => player,
+            None => return,
+        };
+        let taken = player.inventory.add(&item, treasure.count).is_ok();
+        let target = player.id;
+        self.dungeon.treasures[index].taken = taken;
+        self.emit(CombatEvent::FoundItem { target, item: treasure.item, count: treasure.count, taken });
+    }
+
+    /// Brings in the enemies of encounter `index`, the ones that were left
+    /// there if the player got away from it before, and starts a fight.
+    fn start_encounter(&mut self, index: usize, content: &Content) {
+        let parked = self.unpark(index);
+        let enemies = if parked.is_empty() {
+            let names = self.dungeon.encounters[index].enemies.clone();
+            names.iter().filter_map(|name| content.enemy(name)).map(|c| self.spawn(c)).collect()
+        } else {
+            parked
+        };
+        if enemies.is_empty() {
+            self.dungeon.encounters[index].cleared = true;
+            return;
+        }
+        self.combat = Combat::new();
+        self.mode = Mode::Combat { encounter: index };
+        self.emit(CombatEvent::Encountered { enemies });
+    }
+
+    /// Goes back to exploring once the fight is won or the player got away.
+    /// The dead are gone for good, whoever is left waits for the player's return.
+    pub(super) fn end_encounter(&mut self) {
+        let encounter = match self.mode {
+            Mode::Combat { encounter } => encounter,
+            Mode::Exploring => return,
+        };
+        match self.combat.phase {
+            Phase::Victory => self.dungeon.encounters[encounter].cleared = true,
+            Phase::Escaped => self.position = self.previous_position,
+            _ => return,
+        }
+        let enemies: Vec<(CombatantId, bool)> = self.enemies().map(|c| (c.id, c.is_alive())).collect();
+        for (id, alive) in enemies {
+            if alive {
+                self.park(id, encounter);
+            } else {
+                self.leave_fight(id);
+            }
+        }
+        for c in self.combatants_mut().iter_mut() {
+            c.statuses = Default::default();
+            c.cooldowns = Default::default();
+            c.guarding = false;
+        }
+        self.mode = Mode::Exploring;
+    }
+}
@@ -0,0 +1,80 @@
+use super::turnorder::initiative;
+use super::world::{CombatantId, World};
+
+const BASE_CHANCE: i32 = 50;
+/// Percent per point of initiative over the fastest opponent.
+const PER_INITIATIVE: i32 = 2;
+/// Percent lost for every opponent after the first.
+const PER_EXTRA_OPPONENT: i32 = 10;
+const MIN_CHANCE: i32 = 5;
+const MAX_CHANCE: i32 = 95;
+
+/// Percent chance for `actor` to get away, from how much faster they are than
+/// the fastest opponent and how many opponents there are.
+pub fn flee_chance(world: &World, actor: CombatantId) -> i32 {
+    let me = match world.combatant(actor) {
+        Some(me) => me,
+        None => return 0,
+    };
+    let opponents: Vec<i32> = world.combatants().iter()
+        .filter(|c| c.is_alive() && c.faction != me.faction)
+        .map(initiative)
+        .collect();
+    let fastest = match opponents.iter().max() {
+        Some(fastest) => *fastest,
+        None => return 100,
+    };
+    let chance = BASE_CHANCE
+        + (initiative(me) - fastest) * PER_INITIATIVE
+        - (opponents.len() as i32 - 1) * PER_EXTRA_OPPONENT;
+    chance.clamp(MIN_CHANCE, MAX_CHANCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::world::{Attributes, Combatant, Faction};
+
+    fn combatant(faction: Faction, dexterity: i32) -> Combatant {
+        let attributes = Attributes { dexterity, constitution: 5, ..Attributes::default() };
+        Combatant::new("Someone", "Rogue", "Human", faction, attributes)
+    }
+
+    /// A world with the player at `dexterity` against enemies at the given ones.
+    fn chance(dexterity: i32, enemies: &[i32]) -> i32 {
+        let mut world = World::new(1);
+        let hero = world.spawn(combatant(Faction::Player, dexterity));
+        for dexterity in enemies {
+            world.spawn(combatant(Faction::Enemy, *dexterity));
+        }
+        flee_chance(&world, hero)
+    }
+
+    #[test]
+    fn even_odds_against_one_as_fast() {
+        assert_eq!(chance(10, &[10]), BASE_CHANCE);
+    }
+
+    #[test]
+    fn more_enemies_lower_the_chance() {
+        assert!(chance(10, &[10, 5]) < chance(10, &[10]));
+        assert!(chance(10, &[10, 5, 5]) < chance(10, &[10, 5]));
+    }
+
+    #[test]
+    fn speed_raises_the_chance() {
+        assert!(chance(14, &[10]) > chance(10, &[10]));
+        assert!(chance(6, &[10]) < chance(10, &[10]));
+        assert_eq!(chance(10, &[10, 12]), chance(10, &[12, 12]));
+    }
+
+    #[test]
+    fn chance_stays_in_bounds() {
+        assert_eq!(chance(200, &[1]), MAX_CHANCE);
+        assert_eq!(chance(1, &[200, 200, 200, 200]), MIN_CHANCE);
+        for dexterity in 0..40 {
+            let chance = chance(dexterity, &[20, 10]);
+            assert!((MIN_CHANCE..=MAX_CHANCE).contains(&chance));
+        }
+    }
+}
@@ -1,5 +1,273 @@
-use super::World;
+use super::ai;
+use super::combat::{Action, Phase};
+use super::event::CombatEvent;
+use super::explore::Mode;
+use super::resolve::{end_actor_turn, resolve, start_actor_turn};
+use super::turnorder;
+use super::world::{Faction, World};
 
+/// Called once per frame. While fighting, runs the combat state machine until it
+/// needs player input or the fight is over, so idle frames cost nothing.
+/// A won or escaped fight goes back to exploring, exploring waits for the player to move.
+/// A lost fight is the end of the game, it stays as it is until the player starts over.
+pub fn gameloop(world: &mut World) {
+    if world.mode == Mode::Exploring {
+        return;
+    }
+    while step(world) {}
+    if matches!(world.combat.phase, Phase::Victory | Phase::Escaped) {
+        world.end_encounter();
+    }
+}
 
+/// Advances combat by one phase. Returns false when there is nothing to resolve.
+fn step(world: &mut World) -> bool {
+    match world.combat.phase {
+        Phase::StartOfTurn => {
+            world.combat.turn += 1;
+            let turn = world.combat.turn;
+            world.emit(CombatEvent::TurnStarted { turn });
+            world.combat.actors_left = turnorder::round_order(world).into();
+            world.combat.phase = Phase::NextActor;
+        }
+        Phase::NextActor => {
+            let id = match world.combat.actors_left.pop_front() {
+                Some(id) => id,
+                None => {
+                    world.combat.phase = Phase::EndOfTurn;
+                    return true;
+                }
+            };
+            if !world.combatant(id).is_some_and(|c| c.is_alive()) {
+                return true;
+            }
+            let next = if !start_actor_turn(world, id) {
+                end_actor_turn(world, id);
+                Phase::NextActor
+            } else if world.combatant(id).is_some_and(|c| c.is_player()) {
+                Phase::AwaitingInput(id)
+            } else {
+                Phase::EnemyTurn(id)
+            };
+            world.combat.phase = outcome(world).unwrap_or(next);
+        }
+        Phase::AwaitingInput(actor) => match world.combat.take_submitted(actor) {
+            Some(action) => {
+                world.combat.pending = Some(action);
+                world.combat.phase = Phase::Resolving;
+            }
+            None => return false,
+        },
+        Phase::EnemyTurn(actor) => {
+            let (profile, decision) = ai::decide(world, actor);
+            world.emit(CombatEvent::Decided { actor, profile, reason: decision.reason });
+            world.combat.pending = Some(Action { actor, kind: decision.kind });
+            world.combat.phase = Phase::Resolving;
+        }
+        Phase::Resolving => {
+            if let Some(action) = world.combat.pending.take() {
+                let actor = action.actor;
+                resolve(world, action);
+                end_actor_turn(world, actor);
+            }
+            world.combat.phase = outcome(world).unwrap_or(Phase::NextActor);
+        }
+        Phase::EndOfTurn => {
+            end_of_turn_upkeep(world);
+            world.combat.phase = Phase::StartOfTurn;
+        }
+        Phase::Victory | Phase::Defeat | Phase::Escaped => return false,
+    }
+    true
+}
 
-pub fn gameloop(_world: &mut World) {}
\ No newline at end of file
+/// Victory, defeat or escape, if the fight has been decided.
+fn outcome(world: &mut World) -> Option<Phase> {
+    if world.combat.escaped {
+        world.emit(CombatEvent::Escaped);
+        return Some(Phase::Escaped);
+    }
+    let player_alive = world.combatants().iter()
+        .any(|c| c.faction == Faction::Player && c.is_alive());
+    let enemies_alive = world.enemies().any(|c| c.is_alive());
+    let phase = if !player_alive {
+        Phase::Defeat
+    } else if !enemies_alive {
+        Phase::Victory
+    } else {
+        return None;
+    };
+    world.emit(if phase == Phase::Victory { CombatEvent::Victory } else { CombatEvent::Defeat });
+    Some(phase)
+}
+
+fn end_of_turn_upkeep(world: &mut World) {
+    for c in world.combatants_mut().iter_mut().filter(|c| c.is_alive()) {
+        c.stamina.restore(5);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::game::combat::PlayerIntent;
+    use crate::game::content::{Content, NewCharacter, CONTENT_DIR};
+    use crate::game::equipment::EquipSlot;
+    use crate::game::event::FailReason;
+    use crate::game::inventory::InventoryError;
+    use crate::game::world::CombatantId;
+
+    fn content() -> Content {
+        Content::load(Path::new(CONTENT_DIR)).expect("content loads")
+    }
+
+    /// A new game from `seed` with `enemies` to fight right away.
+    fn fight(seed: u64, content: &Content, enemies: &[&str]) -> World {
+        let hero = NewCharacter { name: "Erik".to_string(), class: "Rogue".to_string(), race: "Human".to_string() };
+        let mut world = World::new_game(seed, content, &hero);
+        for name in enemies {
+            world.spawn(content.enemy(name).expect("enemy exists"));
+        }
+        world.mode = Mode::Combat { encounter: 0 };
+        world
+    }
+
+    /// Steps until the fight is over, the player attacking the first living enemy every turn.
+    fn play_out(world: &mut World) -> Vec<CombatEvent> {
+        for _ in 0..10_000 {
+            if step(world) {
+                continue;
+            }
+            let target = world.enemies().find(|e| e.is_alive()).map(|e| e.id);
+            match (world.combat.awaiting_input(), target) {
+                (Some(_), Some(target)) => {
+                    world.submit_intent(PlayerIntent::Attack { target }).expect("a living enemy can be attacked");
+                }
+                _ => break,
+            }
+        }
+        world.drain_events()
+    }
+
+    /// Steps until the state machine waits, returning the phase it stopped in.
+    fn run(world: &mut World) -> Phase {
+        while step(world) {}
+        world.combat.phase
+    }
+
+    fn player_id(world: &World) -> CombatantId {
+        world.player().expect("there is a player").id
+    }
+
+    #[test]
+    fn turns_advance_once_everyone_acted() {
+        let content = content();
+        let mut world = fight(1, &content, &["Rat"]);
+        assert_eq!(run(&mut world), Phase::AwaitingInput(player_id(&world)));
+        assert_eq!(world.combat.turn, 1);
+        assert_eq!(world.submit_intent(PlayerIntent::Wait), Ok(()));
+        assert_eq!(run(&mut world), Phase::AwaitingInput(player_id(&world)));
+        assert_eq!(world.combat.turn, 2);
+        let turns: Vec<CombatEvent> = world.drain_events().into_iter()
+            .filter(|e| matches!(e, CombatEvent::TurnStarted { .. }))
+            .collect();
+        assert_eq!(turns, vec![CombatEvent::TurnStarted { turn: 1 }, CombatEvent::TurnStarted { turn: 2 }]);
+    }
+
+    #[test]
+    fn intents_are_ignored_unless_awaited() {
+        let content = content();
+        let mut world = fight(1, &content, &["Rat"]);
+        assert_eq!(world.combat.phase, Phase::StartOfTurn);
+        assert_eq!(world.submit_intent(PlayerIntent::Wait), Err(FailReason::NotYourTurn));
+        assert_eq!(run(&mut world), Phase::AwaitingInput(player_id(&world)));
+        let before = world.combat.turn;
+        assert_eq!(run(&mut world), Phase::AwaitingInput(player_id(&world)));
+        assert_eq!(world.combat.turn, before);
+    }
+
+    #[test]
+    fn turned_down_intents_keep_the_turn() {
+        let content = content();
+        let mut world = fight(1, &content, &["Rat"]);
+        let me = player_id(&world);
+        let rat = world.enemies().next().unwrap().id;
+        assert_eq!(run(&mut world), Phase::AwaitingInput(me));
+        world.player_mut().unwrap().stamina.current = 0;
+        let stab = PlayerIntent::UseAbility { name: "Stab".to_string(), target: rat };
+        assert_eq!(world.submit_intent(stab), Err(FailReason::NotEnoughStamina));
+        assert_eq!(world.submit_intent(PlayerIntent::Attack { target: me }), Err(FailReason::InvalidTarget));
+        let mend = PlayerIntent::UseAbility { name: "Mend".to_string(), target: me };
+        assert_eq!(world.submit_intent(mend), Err(FailReason::UnknownAbility));
+        assert_eq!(run(&mut world), Phase::AwaitingInput(me));
+        assert_eq!(world.combat.turn, 1);
+        assert!(!world.drain_events().iter().any(|e| matches!(e, CombatEvent::ActionFailed { .. })));
+        assert_eq!(world.submit_intent(PlayerIntent::Attack { target: rat }), Ok(()));
+    }
+
+    #[test]
+    fn victory_when_the_enemies_are_dead() {
+        let content = content();
+        let mut world = fight(1, &content, &["Rat", "Rat"]);
+        for enemy in world.combatants_mut().iter_mut().filter(|c| !c.is_player()) {
+            enemy.hp.current = 0;
+        }
+        assert_eq!(run(&mut world), Phase::Victory);
+        assert!(world.drain_events().contains(&CombatEvent::Victory));
+        assert_eq!(world.submit_intent(PlayerIntent::Wait), Err(FailReason::NotYourTurn));
+    }
+
+    #[test]
+    fn defeat_when_the_player_is_dead() {
+        let content = content();
+        let mut world = fight(1, &content, &["Rat"]);
+        world.player_mut().unwrap().hp.current = 0;
+        assert_eq!(run(&mut world), Phase::Defeat);
+        assert!(world.drain_events().contains(&CombatEvent::Defeat));
+    }
+
+    #[test]
+    fn escaped_once_the_player_gets_away() {
+        let content = content();
+        let mut world = fight(1, &content, &["Rat"]);
+        world.player_mut().unwrap().attributes.dexterity = 200;
+        for _ in 0..100 {
+            if run(&mut world) != Phase::AwaitingInput(player_id(&world)) {
+                break;
+            }
+            world.submit_intent(PlayerIntent::Escape).unwrap();
+        }
+        assert_eq!(world.combat.phase, Phase::Escaped);
+        let events = world.drain_events();
+        assert!(events.contains(&CombatEvent::Escaped));
+        gameloop(&mut world);
+        assert_eq!(world.mode, Mode::Exploring);
+    }
+
+    #[test]
+    fn gear_stays_on_during_a_fight() {
+        let content = content();
+        let mut world = fight(1, &content, &["Rat"]);
+        let slot = EquipSlot::ALL.iter().copied()
+            .find(|s| world.player().unwrap().equipment.get(*s).is_some())
+            .expect("the hero starts with gear on");
+        assert_eq!(world.unequip_player(slot), Err(InventoryError::InCombat));
+        assert_eq!(world.equip_player_item(0), Err(InventoryError::InCombat));
+        assert!(world.player().unwrap().equipment.get(slot).is_some());
+        world.mode = Mode::Exploring;
+        assert_eq!(world.unequip_player(slot), Ok(()));
+    }
+
+    #[test]
+    fn same_seed_same_fight() {
+        let content = content();
+        for seed in 0..10 {
+            let first = play_out(&mut fight(seed, &content, &["Scarecrow", "Rat"]));
+            let second = play_out(&mut fight(seed, &content, &["Scarecrow", "Rat"]));
+            assert!(first.contains(&CombatEvent::Victory) || first.contains(&CombatEvent::Defeat));
+            assert_eq!(first, second, "seed {}", seed);
+        }
+    }
+}
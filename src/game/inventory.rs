@@ -0,0 +1,196 @@
+use super::content::Item;
+
+/// Slots every inventory has. Carrying capacity comes from strength.
+pub const INVENTORY_SLOTS: usize = 12;
+
+/// Weight that can be carried with `strength`, gear included.
+pub fn carry_capacity(strength: i32) -> i32 {
+    20 + strength * 2
+}
+
+/// Some copies of one item in one inventory slot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: String,
+    pub count: u32,
+    /// Weight of one copy, as it was when it was picked up.
+    pub weight: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InventoryError {
+    Full,
+    TooHeavy,
+    NotGear,
+    /// Gear only changes between fights.
+    InCombat,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+    pub max_weight: i32,
+}
+
+impl Inventory {
+    pub fn new(slots: usize, max_weight: i32) -> Self {
+        Inventory { slots: vec![None; slots], max_weight }
+    }
+
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&ItemStack> {
+        self.slots.get(slot).and_then(|s| s.as_ref())
+    }
+
+    pub fn weight(&self) -> i32 {
+        self.slots.iter().flatten().map(|s| s.weight * s.count as i32).sum()
+    }
+
+    /// Adds `count` copies of `item`, topping up existing stacks before taking new slots.
+    /// Adds nothing if they don't all fit.
+    pub fn add(&mut self, item: &Item, count: u32) -> Result<(), InventoryError> {
+        if self.weight() + item.weight * count as i32 > self.max_weight {
+            return Err(InventoryError::TooHeavy);
+        }
+        let stack = item.stack.max(1);
+        let room: u32 = self.slots.iter()
+            .map(|s| match s {
+                Some(s) if s.item == item.name => stack.saturating_sub(s.count),
+                Some(_) => 0,
+                None => stack,
+            })
+            .sum();
+        if room < count {
+            return Err(InventoryError::Full);
+        }
+        let mut left = count;
+        for s in self.slots.iter_mut().flatten().filter(|s| s.item == item.name) {
+            let added = left.min(stack.saturating_sub(s.count));
+            s.count += added;
+            left -= added;
+        }
+        for s in self.slots.iter_mut().filter(|s| s.is_none()) {
+            if left == 0 {
+                break;
+            }
+            let added = left.min(stack);
+            *s = Some(ItemStack { item: item.name.clone(), count: added, weight: item.weight });
+            left -= added;
+        }
+        Ok(())
+    }
+
+    /// Takes one copy out of `slot`, returning the name of the item.
+    pub fn take_one(&mut self, slot: usize) -> Option<String> {
+        let s = self.slots.get_mut(slot)?;
+        let stack = s.as_mut()?;
+        let item = stack.item.clone();
+        stack.count -= 1;
+        if stack.count == 0 {
+            *s = None;
+        }
+        Some(item)
+    }
+
+    /// Moves what is in `from` to `to`. Stacks of the same item are merged
+    /// as far as `stack` allows, anything else swaps places.
+    pub fn move_slot(&mut self, from: usize, to: usize, stack: u32) {
+        if from == to || from >= self.slots.len() || to >= self.slots.len() {
+            return;
+        }
+        let same_item = match (&self.slots[from], &self.slots[to]) {
+            (Some(a), Some(b)) => a.item == b.item,
+            _ => false,
+        };
+        if !same_item {
+            self.slots.swap(from, to);
+            return;
+        }
+        let moved = match (&self.slots[from], &self.slots[to]) {
+            (Some(a), Some(b)) => a.count.min(stack.saturating_sub(b.count)),
+            _ => 0,
+        };
+        if let Some(b) = self.slots[to].as_mut() {
+            b.count += moved;
+        }
+        if let Some(a) = self.slots[from].as_mut() {
+            a.count -= moved;
+            if a.count == 0 {
+                self.slots[from] = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, weight: i32, stack: u32) -> Item {
+        ron::de::from_str(&format!("(name: {:?}, weight: {}, stack: {})", name, weight, stack)).expect("valid item")
+    }
+
+    fn counts(inventory: &Inventory) -> Vec<Option<(String, u32)>> {
+        inventory.slots().iter().map(|s| s.as_ref().map(|s| (s.item.clone(), s.count))).collect()
+    }
+
+    #[test]
+    fn too_heavy_adds_nothing() {
+        let mut inventory = Inventory::new(4, 10);
+        let rock = item("Rock", 4, 5);
+        assert_eq!(inventory.add(&rock, 2), Ok(()));
+        assert_eq!(inventory.add(&rock, 1), Err(InventoryError::TooHeavy));
+        assert_eq!(inventory.weight(), 8);
+    }
+
+    #[test]
+    fn full_adds_nothing() {
+        let mut inventory = Inventory::new(2, 100);
+        let potion = item("Potion", 1, 3);
+        assert_eq!(inventory.add(&potion, 5), Ok(()));
+        assert_eq!(inventory.add(&potion, 2), Err(InventoryError::Full));
+        assert_eq!(inventory.add(&item("Sword", 5, 1), 1), Err(InventoryError::Full));
+        assert_eq!(counts(&inventory), vec![Some(("Potion".to_string(), 3)), Some(("Potion".to_string(), 2))]);
+    }
+
+    #[test]
+    fn adding_tops_up_stacks_first() {
+        let mut inventory = Inventory::new(3, 100);
+        let potion = item("Potion", 1, 3);
+        inventory.add(&potion, 2).unwrap();
+        inventory.add(&item("Sword", 5, 1), 1).unwrap();
+        inventory.add(&potion, 2).unwrap();
+        assert_eq!(counts(&inventory), vec![
+            Some(("Potion".to_string(), 3)),
+            Some(("Sword".to_string(), 1)),
+            Some(("Potion".to_string(), 1)),
+        ]);
+    }
+
+    #[test]
+    fn moving_onto_the_same_item_merges() {
+        let mut inventory = Inventory::new(3, 100);
+        let potion = item("Potion", 1, 3);
+        inventory.add(&potion, 3).unwrap();
+        inventory.add(&potion, 2).unwrap();
+        inventory.take_one(0);
+        inventory.move_slot(1, 0, 3);
+        assert_eq!(counts(&inventory), vec![Some(("Potion".to_string(), 3)), Some(("Potion".to_string(), 1)), None]);
+        inventory.move_slot(1, 0, 3);
+        assert_eq!(inventory.get(1).map(|s| s.count), Some(1));
+    }
+
+    #[test]
+    fn moving_onto_something_else_swaps() {
+        let mut inventory = Inventory::new(3, 100);
+        inventory.add(&item("Potion", 1, 3), 2).unwrap();
+        inventory.add(&item("Sword", 5, 1), 1).unwrap();
+        inventory.move_slot(0, 1, 3);
+        assert_eq!(counts(&inventory), vec![Some(("Sword".to_string(), 1)), Some(("Potion".to_string(), 2)), None]);
+        inventory.move_slot(1, 2, 3);
+        assert_eq!(counts(&inventory), vec![Some(("Sword".to_string(), 1)), None, Some(("Potion".to_string(), 2))]);
+    }
+}
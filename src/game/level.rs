@@ -0,0 +1,157 @@
+use serde::Deserialize;
+
+use super::stats::Stat;
+use super::world::{Attributes, Combatant};
+
+/// How characters grow, as read from `levels.ron`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LevelCurve {
+    /// Total XP needed for each level from 2 on, the last one is the highest level.
+    pub xp: Vec<u32>,
+    /// Attribute points handed out on every level up.
+    pub attribute_points: u32,
+    /// Levels at which a new ability can be picked.
+    pub ability_levels: Vec<u32>,
+}
+
+impl LevelCurve {
+    /// Total XP needed for `level`, None past the highest level.
+    pub fn xp_for(&self, level: u32) -> Option<u32> {
+        match level {
+            0 | 1 => Some(0),
+            _ => self.xp.get(level as usize - 2).copied(),
+        }
+    }
+
+    pub fn level_for(&self, xp: u32) -> u32 {
+        1 + self.xp.iter().take_while(|needed| xp >= **needed).count() as u32
+    }
+}
+
+fn attribute_mut(attributes: &mut Attributes, stat: Stat) -> Option<&mut i32> {
+    match stat {
+        Stat::Strength => Some(&mut attributes.strength),
+        Stat::Dexterity => Some(&mut attributes.dexterity),
+        Stat::Constitution => Some(&mut attributes.constitution),
+        Stat::Intelligence => Some(&mut attributes.intelligence),
+        Stat::Wisdom => Some(&mut attributes.wisdom),
+        _ => None,
+    }
+}
+
+impl Combatant {
+    /// Adds `amount` XP and levels up as far as `curve` allows,
+    /// growing attributes and pools on each level. Returns the levels reached, in order.
+    pub fn gain_xp(&mut self, amount: u32, curve: &LevelCurve) -> Vec<u32> {
+        self.xp += amount;
+        let mut reached = Vec::new();
+        while self.level < curve.level_for(self.xp) {
+            self.level += 1;
+            self.attributes = self.attributes + self.growth;
+            self.attribute_points += curve.attribute_points;
+            if curve.ability_levels.contains(&self.level) {
+                self.ability_picks += 1;
+            }
+            reached.push(self.level);
+        }
+        self.grow_pools();
+        reached
+    }
+
+    /// Spends an attribute point on `stat`, which has to be an attribute.
+    /// Pools grow with it, so a point in CON is HP right away.
+    pub fn raise_attribute(&mut self, stat: Stat) -> bool {
+        if self.attribute_points == 0 {
+            return false;
+        }
+        match attribute_mut(&mut self.attributes, stat) {
+            Some(value) => *value += 1,
+            None => return false,
+        }
+        self.attribute_points -= 1;
+        self.grow_pools();
+        true
+    }
+
+    /// Abilities of the class pool not known yet.
+    pub fn learnable(&self) -> impl Iterator<Item = &String> {
+        self.ability_pool.iter().filter(move |a| !self.abilities.contains(a))
+    }
+
+    /// Spends an ability pick on `ability` from the class pool.
+    pub fn learn(&mut self, ability: &str) -> bool {
+        if self.ability_picks == 0 || !self.learnable().any(|a| a == ability) {
+            return false;
+        }
+        self.ability_picks -= 1;
+        self.abilities.push(ability.to_string());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::world::Faction;
+
+    fn curve() -> LevelCurve {
+        LevelCurve { xp: vec![100, 250, 450], attribute_points: 3, ability_levels: vec![3] }
+    }
+
+    fn hero() -> Combatant {
+        let attributes = Attributes { strength: 10, constitution: 10, intelligence: 5, ..Attributes::default() };
+        let mut c = Combatant::new("Erik", "Rogue", "Human", Faction::Player, attributes);
+        c.growth = Attributes { constitution: 1, strength: 2, ..Attributes::default() };
+        c.ability_pool = vec!["Scurry".to_string()];
+        c
+    }
+
+    #[test]
+    fn levels_follow_the_curve() {
+        let curve = curve();
+        assert_eq!(curve.level_for(0), 1);
+        assert_eq!(curve.level_for(99), 1);
+        assert_eq!(curve.level_for(100), 2);
+        assert_eq!(curve.level_for(449), 3);
+        assert_eq!(curve.level_for(10_000), 4);
+        assert_eq!(curve.xp_for(1), Some(0));
+        assert_eq!(curve.xp_for(3), Some(250));
+        assert_eq!(curve.xp_for(5), None);
+    }
+
+    #[test]
+    fn one_gain_can_reach_several_levels() {
+        let mut c = hero();
+        assert_eq!(c.gain_xp(50, &curve()), Vec::<u32>::new());
+        assert_eq!(c.gain_xp(300, &curve()), vec![2, 3]);
+        assert_eq!((c.level, c.xp), (3, 350));
+        assert_eq!(c.attributes.strength, 14);
+        assert_eq!(c.attribute_points, 6);
+        assert_eq!(c.ability_picks, 1);
+    }
+
+    #[test]
+    fn leveling_grows_the_pools() {
+        let mut c = hero();
+        c.hp.drain(20);
+        let (max, current) = (c.hp.max, c.hp.current);
+        c.gain_xp(100, &curve());
+        assert_eq!(c.hp.max, max + 10);
+        assert_eq!(c.hp.current, current + 10);
+        assert!(c.raise_attribute(Stat::Constitution));
+        assert_eq!(c.hp.max, max + 20);
+        assert!(!c.raise_attribute(Stat::Spellpower));
+    }
+
+    #[test]
+    fn points_and_picks_run_out() {
+        let mut c = hero();
+        assert!(!c.raise_attribute(Stat::Strength));
+        assert!(!c.learn("Scurry"));
+        c.gain_xp(250, &curve());
+        assert!(c.learn("Scurry"));
+        assert!(!c.learn("Scurry"));
+        assert_eq!(c.learnable().count(), 0);
+    }
+}
@@ -1,5 +1,24 @@
 pub use self::gameloop::*;
 pub use self::world::*;
 
+pub mod ability;
+pub mod ai;
+pub mod attack;
+pub mod combat;
+pub mod content;
+pub mod damage;
+pub mod dungeon;
+pub mod equipment;
+pub mod event;
+pub mod explore;
+pub mod flee;
 pub mod gameloop;
-pub mod world;
\ No newline at end of file
+pub mod inventory;
+pub mod level;
+pub mod resolve;
+pub mod rng;
+pub mod stance;
+pub mod stats;
+pub mod status;
+pub mod turnorder;
+pub mod world;
@@ -0,0 +1,361 @@
+use super::ability::{Ability, Targeting};
+use super::attack::{roll_attack, AttackCategory, AttackOutcome, CRIT_DAMAGE_PERCENT};
+use super::combat::{Action, ActionKind};
+use super::damage::{mitigate, Damage};
+use super::event::{CombatEvent, DamageCause, FailReason};
+use super::flee::flee_chance;
+use super::status::{StatusEffect, StatusKind, TickTiming};
+use super::world::{CombatantId, World};
+
+/// Why `action` can't be done right now, if anything stops it. The player's actions
+/// are checked before they are queued, everyone's again when they resolve.
+pub(super) fn check_action(world: &World, action: &Action) -> Result<(), FailReason> {
+    let user = match world.combatant(action.actor) {
+        Some(user) => user,
+        None => return Ok(()),
+    };
+    let aim = |ability: &Ability, target| match targets_of(world, action.actor, ability, target) {
+        Some(_) => Ok(()),
+        None => Err(FailReason::InvalidTarget),
+    };
+    match &action.kind {
+        ActionKind::Attack { target } => match world.combatant(*target) {
+            Some(t) if t.is_alive() && t.faction != user.faction => Ok(()),
+            _ => Err(FailReason::InvalidTarget),
+        },
+        ActionKind::Ability { name, target } => {
+            let ability = world.ability(name).ok_or(FailReason::UnknownAbility)?;
+            user.check_ability(ability)?;
+            aim(ability, *target)
+        }
+        ActionKind::UseItem { slot, target } => {
+            let ability = item_ability(world, action.actor, *slot).ok_or(FailReason::NotUsable)?;
+            aim(ability, *target)
+        }
+        ActionKind::Block | ActionKind::ChangeStance { .. } | ActionKind::Flee | ActionKind::Wait => Ok(()),
+    }
+}
+
+pub(super) fn resolve(world: &mut World, action: Action) {
+    let actor = action.actor;
+    let (strength, weapon_damage) = match world.combatant(actor) {
+        Some(c) => (c.effective_attributes().strength, c.equipment.attack_damage_type()),
+        None => return,
+    };
+    let checked = check_action(world, &action);
+    let fail = |world: &mut World, ability: &str, reason| {
+        world.emit(CombatEvent::ActionFailed { actor, ability: ability.to_string(), reason });
+    };
+    match action.kind {
+        ActionKind::Attack { target } => {
+            if let Err(reason) = checked {
+                return fail(world, "Attack", reason);
+            }
+            let damage = Damage::new(weapon_damage, (strength / 2).max(1));
+            world.emit(CombatEvent::AbilityUsed { actor, ability: "Attack".to_string(), target: Some(target) });
+            attack(world, actor, target, AttackCategory::Melee, &damage, "Attack", &[]);
+        }
+        ActionKind::Ability { name, target } => {
+            if let Err(reason) = checked {
+                return fail(world, &name, reason);
+            }
+            let ability = match world.ability(&name) {
+                Some(ability) => ability.clone(),
+                None => return,
+            };
+            let targets = targets_of(world, actor, &ability, target).unwrap_or_default();
+            if let Some(c) = world.combatant_mut(actor) {
+                c.mp.drain(ability.mp_cost);
+                c.stamina.drain(ability.stamina_cost);
+                c.cooldowns.start(&ability);
+            }
+            let target = shown_target(&ability, target);
+            world.emit(CombatEvent::AbilityUsed { actor, ability: name.clone(), target });
+            apply_ability(world, actor, &ability, targets);
+        }
+        ActionKind::UseItem { slot, target } => {
+            let item = world.combatant(actor)
+                .and_then(|c| c.inventory.get(slot))
+                .map(|s| s.item.clone())
+                .unwrap_or_else(|| "nothing".to_string());
+            if let Err(reason) = checked {
+                return fail(world, &item, reason);
+            }
+            let ability = match item_ability(world, actor, slot) {
+                Some(ability) => ability.clone(),
+                None => return,
+            };
+            let targets = targets_of(world, actor, &ability, target).unwrap_or_default();
+            if let Some(c) = world.combatant_mut(actor) {
+                c.inventory.take_one(slot);
+            }
+            let target = shown_target(&ability, target);
+            world.emit(CombatEvent::ItemUsed { actor, item: item.clone(), target });
+            apply_ability(world, actor, &ability, targets);
+        }
+        ActionKind::Block => {
+            if let Some(c) = world.combatant_mut(actor) {
+                c.guarding = true;
+            }
+            world.emit(CombatEvent::Guarded { actor });
+        }
+        ActionKind::ChangeStance { stance } => {
+            if let Some(c) = world.combatant_mut(actor) {
+                c.stance = stance;
+            }
+            world.emit(CombatEvent::StanceChanged { actor, stance });
+        }
+        ActionKind::Flee => {
+            let chance = flee_chance(world, actor);
+            if world.rng.d100() >= chance {
+                world.emit(CombatEvent::FleeFailed { actor, chance });
+                return;
+            }
+            world.emit(CombatEvent::Fled { actor, chance });
+            if world.combatant(actor).is_some_and(|c| c.is_player()) {
+                world.combat.escaped = true;
+            } else {
+                world.leave_fight(actor);
+            }
+        }
+        ActionKind::Wait => world.emit(CombatEvent::Waited { actor }),
+    }
+}
+
+/// The ability the item in inventory `slot` of `actor` casts. Items cast it for free,
+/// no matter what the user knows.
+fn item_ability(world: &World, actor: CombatantId, slot: usize) -> Option<&Ability> {
+    let stack = world.combatant(actor)?.inventory.get(slot)?;
+    world.item(&stack.item)?.ability.as_ref().and_then(|a| world.ability(a))
+}
+
+/// Who `ability` hits when `actor` aims it at `target`, None if that is not a valid pick.
+fn targets_of(world: &World, actor: CombatantId, ability: &Ability, target: CombatantId) -> Option<Vec<CombatantId>> {
+    let user = world.combatant(actor)?;
+    let targets = match ability.targeting {
+        Targeting::User => vec![actor],
+        Targeting::AllEnemies => world.combatants().iter()
+            .filter(|c| c.is_alive() && c.faction != user.faction)
+            .map(|c| c.id)
+            .collect(),
+        Targeting::Enemy | Targeting::Ally => match world.combatant(target) {
+            Some(t) if user.can_target(ability, t) => vec![target],
+            _ => return None,
+        },
+    };
+    Some(targets)
+}
+
+/// The target worth mentioning in the log, abilities that pick their own targets have none.
+fn shown_target(ability: &Ability, target: CombatantId) -> Option<CombatantId> {
+    match ability.targeting {
+        Targeting::Enemy | Targeting::Ally => Some(target),
+        Targeting::User | Targeting::AllEnemies => None,
+    }
+}
+
+/// Everything `ability` does once it has been paid for.
+fn apply_ability(world: &mut World, actor: CombatantId, ability: &Ability, targets: Vec<CombatantId>) {
+    let (damage, heal) = match world.combatant(actor) {
+        Some(user) => (ability.damage_for(user), ability.heal_for(user)),
+        None => return,
+    };
+    for status in &ability.on_self {
+        apply_status(world, actor, status.by(actor));
+    }
+    for target in targets {
+        if let Some(amount) = heal {
+            restore_hp(world, actor, target, amount, &ability.name);
+        }
+        match &damage {
+            Some(damage) => attack(world, actor, target, ability.category, damage, &ability.name, &ability.on_hit),
+            None => for status in &ability.on_hit {
+                apply_status(world, target, status.by(actor));
+            },
+        }
+    }
+}
+
+fn attack(
+    world: &mut World,
+    actor: CombatantId,
+    target: CombatantId,
+    category: AttackCategory,
+    damage: &Damage,
+    ability: &str,
+    on_hit: &[StatusEffect],
+) {
+    let (attacker, defender) = match (world.combatant(actor), world.combatant(target)) {
+        (Some(a), Some(d)) if d.is_alive() => (a.ratings(), d.ratings()),
+        _ => return,
+    };
+    let cause = DamageCause::Ability(ability.to_string());
+    let outcome = roll_attack(category, &attacker, &defender, || world.rng.d100());
+    match outcome {
+        AttackOutcome::Miss => {
+            world.emit(CombatEvent::Missed { actor, target, ability: ability.to_string() });
+            return;
+        }
+        AttackOutcome::Hit => deal_damage(world, Some(actor), target, damage, cause, false, true),
+        AttackOutcome::Crit => {
+            let damage = damage.scaled(CRIT_DAMAGE_PERCENT);
+            deal_damage(world, Some(actor), target, &damage, cause, true, true)
+        }
+    }
+    for status in on_hit {
+        apply_status(world, target, status.by(actor));
+    }
+}
+
+fn deal_damage(
+    world: &mut World,
+    source: Option<CombatantId>,
+    target: CombatantId,
+    damage: &Damage,
+    cause: DamageCause,
+    crit: bool,
+    blockable: bool,
+) {
+    let result = match world.combatant_mut(target) {
+        Some(defender) if defender.is_alive() => {
+            let modifiers = defender.modifiers();
+            let block = if blockable { modifiers.block } else { 0 };
+            let result = mitigate(damage, &modifiers.resistances, block);
+            defender.hp.drain(result.taken());
+            result
+        }
+        _ => return,
+    };
+    world.emit(CombatEvent::Damaged { source, target, cause, crit, result });
+    if world.combatant(target).is_some_and(|c| !c.is_alive()) {
+        world.emit(CombatEvent::Died { target });
+        world.award_xp(target);
+    }
+}
+
+fn restore_hp(world: &mut World, source: CombatantId, target: CombatantId, amount: i32, ability: &str) {
+    let amount = match world.combatant_mut(target) {
+        Some(c) if c.is_alive() => c.hp.restore(amount),
+        _ => return,
+    };
+    world.emit(CombatEvent::Healed { source, target, ability: ability.to_string(), amount });
+}
+
+fn apply_status(world: &mut World, target: CombatantId, status: StatusEffect) {
+    let stacks = world.combatant_mut(target)
+        .filter(|c| c.is_alive())
+        .and_then(|c| if status.source == Some(c.id) { c.statuses.apply_own(status) } else { c.statuses.apply(status) });
+    if let Some(stacks) = stacks {
+        world.emit(CombatEvent::StatusApplied { target, status: status.kind, stacks });
+    }
+}
+
+/// Damage over time ticks for `owner` at `timing`. Each tick is its own event
+/// and statuses are not blockable.
+fn tick_statuses(world: &mut World, owner: CombatantId, timing: TickTiming) {
+    let ticks = match world.combatant(owner) {
+        Some(c) if c.is_alive() => c.statuses.ticks(timing),
+        _ => return,
+    };
+    for tick in ticks {
+        let source = world.combatant(owner)
+            .and_then(|c| c.statuses.iter().find(|s| s.kind == tick.kind))
+            .and_then(|s| s.source);
+        deal_damage(world, source, owner, &tick.damage, DamageCause::Status(tick.kind), false, false);
+    }
+}
+
+/// Ends the block of `actor` and runs their start of turn statuses.
+/// Returns whether they are still able to act this turn.
+pub(super) fn start_actor_turn(world: &mut World, actor: CombatantId) -> bool {
+    if let Some(c) = world.combatant_mut(actor) {
+        c.guarding = false;
+    }
+    tick_statuses(world, actor, TickTiming::StartOfTurn);
+    let (alive, stunned) = match world.combatant(actor) {
+        Some(c) => (c.is_alive(), c.statuses.has(StatusKind::Stun)),
+        None => return false,
+    };
+    if alive && stunned {
+        world.emit(CombatEvent::TurnLost { actor, status: StatusKind::Stun });
+    }
+    alive && !stunned
+}
+
+/// Runs end of turn statuses for `actor` and counts their durations and cooldowns down.
+pub(super) fn end_actor_turn(world: &mut World, actor: CombatantId) {
+    tick_statuses(world, actor, TickTiming::EndOfTurn);
+    let expired = match world.combatant_mut(actor) {
+        Some(c) if c.is_alive() => {
+            c.cooldowns.count_down();
+            c.statuses.count_down()
+        }
+        _ => return,
+    };
+    for status in expired {
+        world.emit(CombatEvent::StatusExpired { target: actor, status });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::damage::DamageType;
+    use crate::game::world::{Attributes, Combatant, Faction};
+
+    fn duel(seed: u64) -> (World, CombatantId, CombatantId) {
+        let mut world = World::new(seed);
+        let attributes = Attributes { strength: 8, dexterity: 8, constitution: 50, ..Attributes::default() };
+        let hero = world.spawn(Combatant::new("Erik", "Rogue", "Human", Faction::Player, attributes));
+        let dummy = world.spawn(Combatant::new("Dummy", "Warrior", "Construct", Faction::Enemy, attributes));
+        (world, hero, dummy)
+    }
+
+    fn ability(category: &str) -> Ability {
+        let source = format!("(name: \"Jab\", category: {}, targeting: Enemy, scaling: Strength)", category);
+        ron::de::from_str(&source).expect("valid ability")
+    }
+
+    #[test]
+    fn stun_takes_the_turn() {
+        let (mut world, hero, _) = duel(1);
+        assert!(start_actor_turn(&mut world, hero));
+        apply_status(&mut world, hero, StatusEffect::new(StatusKind::Stun, 1, 0));
+        world.drain_events();
+        assert!(!start_actor_turn(&mut world, hero));
+        assert_eq!(world.drain_events(), vec![CombatEvent::TurnLost { actor: hero, status: StatusKind::Stun }]);
+        end_actor_turn(&mut world, hero);
+        assert!(start_actor_turn(&mut world, hero));
+    }
+
+    #[test]
+    fn silence_stops_spells_only() {
+        let (mut world, hero, _) = duel(1);
+        apply_status(&mut world, hero, StatusEffect::new(StatusKind::Silence, 2, 0));
+        world.combatant_mut(hero).unwrap().abilities.push("Jab".to_string());
+        let hero = world.combatant(hero).unwrap();
+        assert_eq!(hero.check_ability(&ability("Spell")), Err(FailReason::Silenced));
+        assert_eq!(hero.check_ability(&ability("Melee")), Ok(()));
+    }
+
+    #[test]
+    fn crits_deal_more_damage() {
+        let (mut crits, mut hits) = (0, 0);
+        for seed in 0..200 {
+            let (mut world, hero, dummy) = duel(seed);
+            attack(&mut world, hero, dummy, AttackCategory::Melee, &Damage::new(DamageType::Slash, 20), "Attack", &[]);
+            for event in world.drain_events() {
+                if let CombatEvent::Damaged { crit, result, .. } = event {
+                    if crit {
+                        crits += 1;
+                        assert_eq!(result.raw(), 20 * CRIT_DAMAGE_PERCENT / 100);
+                    } else {
+                        hits += 1;
+                        assert_eq!(result.raw(), 20);
+                    }
+                }
+            }
+        }
+        assert!(crits > 0 && hits > crits, "{} crits, {} hits", crits, hits);
+    }
+}
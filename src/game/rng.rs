@@ -0,0 +1,76 @@
+/// Seedable random number generator owned by `World`.
+///
+/// Every random decision in the game goes through this, so replaying a fight
+/// with the same seed and the same player input gives the same result.
+/// The algorithm is SplitMix64, which is small and good enough for dice.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    seed: u64,
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { seed, state: seed }
+    }
+
+    /// The seed this generator was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `low..high`. Returns `low` if the range is empty.
+    pub fn range(&mut self, low: i32, high: i32) -> i32 {
+        if high <= low {
+            return low;
+        }
+        let span = (high as i64 - low as i64) as u64;
+        (low as i64 + (self.next_u64() % span) as i64) as i32
+    }
+
+    /// Uniform in `0..100`.
+    pub fn d100(&mut self) -> i32 {
+        self.range(0, 100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_rolls() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+            assert_eq!(a.range(-5, 17), b.range(-5, 17));
+            assert_eq!(a.d100(), b.d100());
+        }
+    }
+
+    #[test]
+    fn other_seed_other_rolls() {
+        let (mut a, mut b) = (Rng::new(1), Rng::new(2));
+        let a: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        let b: Vec<u64> = (0..8).map(|_| b.next_u64()).collect();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn range_stays_in_bounds() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let roll = rng.range(3, 9);
+            assert!((3..9).contains(&roll));
+        }
+        assert_eq!(rng.range(5, 5), 5);
+    }
+}
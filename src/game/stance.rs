@@ -0,0 +1,72 @@
+use serde::Deserialize;
+
+use super::attack::{Rating, Ratings};
+use super::stats::Modifiers;
+use super::world::Combatant;
+
+/// How a combatant fights. Kept until changed, which takes a turn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Stance {
+    #[default]
+    Balanced,
+    /// Harder to hit and to hurt, easier to miss with.
+    Defensive,
+    /// More hits and crits, at the cost of defense.
+    Aggressive,
+}
+
+impl Stance {
+    pub const ALL: [Stance; 3] = [Stance::Balanced, Stance::Defensive, Stance::Aggressive];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stance::Balanced => "Balanced",
+            Stance::Defensive => "Defensive",
+            Stance::Aggressive => "Aggressive",
+        }
+    }
+
+    pub fn modifiers(self) -> Modifiers {
+        match self {
+            Stance::Balanced => Modifiers::default(),
+            Stance::Defensive => Modifiers {
+                ratings: Ratings {
+                    melee: Rating { hit: -10, crit: 0 },
+                    ranged: Rating { hit: -10, crit: 0 },
+                    evasion: 10,
+                    willpower: 5,
+                    ..Ratings::default()
+                },
+                block: 3,
+                ..Modifiers::default()
+            },
+            Stance::Aggressive => Modifiers {
+                ratings: Ratings {
+                    melee: Rating { hit: 5, crit: 10 },
+                    ranged: Rating { hit: 5, crit: 10 },
+                    evasion: -10,
+                    ..Ratings::default()
+                },
+                block: -2,
+                ..Modifiers::default()
+            },
+        }
+    }
+}
+
+/// What the Block action adds until the blocker's next turn.
+pub fn guard_modifiers() -> Modifiers {
+    Modifiers {
+        ratings: Ratings { evasion: 15, ..Ratings::default() },
+        block: 6,
+        ..Modifiers::default()
+    }
+}
+
+impl Combatant {
+    /// Modifiers from the stance, and from blocking if they are.
+    pub fn stance_modifiers(&self) -> Modifiers {
+        let guard = if self.guarding { guard_modifiers() } else { Modifiers::default() };
+        self.stance.modifiers() + guard
+    }
+}
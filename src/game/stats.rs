@@ -0,0 +1,148 @@
+use serde::Deserialize;
+
+use super::attack::{AttackCategory, Ratings};
+use super::damage::{DamageType, Resistances};
+use super::world::{Attributes, Combatant};
+
+/// Everything shown on the character sheet that can be broken down by source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum Stat {
+    Strength,
+    Dexterity,
+    Constitution,
+    Intelligence,
+    Wisdom,
+    Spellpower,
+    Hit(AttackCategory),
+    Crit(AttackCategory),
+    Resistance(DamageType),
+    Block,
+    Initiative,
+}
+
+impl Stat {
+    pub const ATTRIBUTES: [Stat; 5] = [Stat::Strength, Stat::Dexterity, Stat::Constitution, Stat::Intelligence, Stat::Wisdom];
+
+    pub fn name(self) -> String {
+        match self {
+            Stat::Strength => "STR".to_string(),
+            Stat::Dexterity => "DEX".to_string(),
+            Stat::Constitution => "CON".to_string(),
+            Stat::Intelligence => "INT".to_string(),
+            Stat::Wisdom => "WIS".to_string(),
+            Stat::Spellpower => "Spellpower".to_string(),
+            Stat::Hit(category) => format!("{} Hit", category.name()),
+            Stat::Crit(category) => format!("{} Crit", category.name()),
+            Stat::Resistance(damage_type) => damage_type.name().to_string(),
+            Stat::Block => "Block".to_string(),
+            Stat::Initiative => "Initiative".to_string(),
+        }
+    }
+}
+
+/// Flat bonuses from one source, like a piece of gear or a buff.
+/// Gear in `items.ron` lists only the ones it changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Modifiers {
+    pub attributes: Attributes,
+    pub ratings: Ratings,
+    pub resistances: Resistances,
+    pub block: i32,
+    /// Percent added to speed, negative when slowed.
+    pub haste: i32,
+}
+
+impl std::ops::Add for Modifiers {
+    type Output = Modifiers;
+
+    fn add(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            attributes: self.attributes + other.attributes,
+            ratings: self.ratings + other.ratings,
+            resistances: self.resistances + other.resistances,
+            block: self.block + other.block,
+            haste: self.haste + other.haste,
+        }
+    }
+}
+
+/// Where the value of a stat comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Breakdown {
+    pub base: i32,
+    pub gear: i32,
+    pub buffs: i32,
+}
+
+impl Breakdown {
+    pub fn total(&self) -> i32 {
+        self.base + self.gear + self.buffs
+    }
+}
+
+pub fn initiative(dexterity: i32, haste: i32) -> i32 {
+    (10 + dexterity * 2) * (100 + haste).max(10) / 100
+}
+
+fn value(stat: Stat, base: &Attributes, m: &Modifiers) -> i32 {
+    let a = *base + m.attributes;
+    let r = Ratings::derive(&a) + m.ratings;
+    match stat {
+        Stat::Strength => a.strength,
+        Stat::Dexterity => a.dexterity,
+        Stat::Constitution => a.constitution,
+        Stat::Intelligence => a.intelligence,
+        Stat::Wisdom => a.wisdom,
+        Stat::Spellpower => r.spellpower,
+        Stat::Hit(category) => r.rating(category).hit,
+        Stat::Crit(category) => r.rating(category).crit,
+        Stat::Resistance(damage_type) => m.resistances.effective(damage_type),
+        Stat::Block => m.block,
+        Stat::Initiative => initiative(a.dexterity, m.haste),
+    }
+}
+
+impl Combatant {
+    /// Innate modifiers, the ones that come with the combatant itself.
+    pub fn base_modifiers(&self) -> Modifiers {
+        Modifiers { resistances: self.resistances, block: self.block, ..Modifiers::default() }
+    }
+
+    pub fn gear_modifiers(&self) -> Modifiers {
+        self.equipment.modifiers()
+    }
+
+    /// Statuses, stance and blocking, everything that comes and goes in a fight.
+    pub fn buff_modifiers(&self) -> Modifiers {
+        self.statuses.modifiers() + self.stance_modifiers()
+    }
+
+    /// All modifiers added together.
+    pub fn modifiers(&self) -> Modifiers {
+        self.base_modifiers() + self.gear_modifiers() + self.buff_modifiers()
+    }
+
+    pub fn breakdown(&self, stat: Stat) -> Breakdown {
+        let base_mods = self.base_modifiers();
+        let gear_mods = base_mods + self.gear_modifiers();
+        let all_mods = gear_mods + self.buff_modifiers();
+        let base = value(stat, &self.attributes, &base_mods);
+        let with_gear = value(stat, &self.attributes, &gear_mods);
+        let total = value(stat, &self.attributes, &all_mods);
+        Breakdown { base, gear: with_gear - base, buffs: total - with_gear }
+    }
+
+    pub fn stat(&self, stat: Stat) -> i32 {
+        value(stat, &self.attributes, &self.modifiers())
+    }
+
+    pub fn effective_attributes(&self) -> Attributes {
+        self.attributes + self.modifiers().attributes
+    }
+
+    pub fn ratings(&self) -> Ratings {
+        let m = self.modifiers();
+        Ratings::derive(&(self.attributes + m.attributes)) + m.ratings
+    }
+}
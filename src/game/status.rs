@@ -0,0 +1,303 @@
+use serde::Deserialize;
+
+use super::damage::{Damage, DamageType};
+use super::stats::Modifiers;
+use super::world::CombatantId;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum StatusKind {
+    Bleed,
+    Burn,
+    Poison,
+    Stun,
+    Silence,
+    Haste,
+    Slow,
+}
+
+/// What happens when a status is applied to someone who already has it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stacking {
+    /// Reset the duration and keep the stronger potency.
+    Refresh,
+    /// Add a stack, up to `max`, and reset the duration.
+    Stack { max: u32 },
+    /// Leave the existing status alone.
+    Ignore,
+}
+
+/// When a status does its thing, relative to its owner's turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TickTiming {
+    StartOfTurn,
+    EndOfTurn,
+    /// Only has an effect while present, like stun or haste.
+    Never,
+}
+
+impl StatusKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusKind::Bleed => "Bleed",
+            StatusKind::Burn => "Burn",
+            StatusKind::Poison => "Poison",
+            StatusKind::Stun => "Stun",
+            StatusKind::Silence => "Silence",
+            StatusKind::Haste => "Haste",
+            StatusKind::Slow => "Slow",
+        }
+    }
+
+    pub fn stacking(self) -> Stacking {
+        match self {
+            StatusKind::Bleed => Stacking::Stack { max: 5 },
+            StatusKind::Poison => Stacking::Stack { max: 3 },
+            StatusKind::Stun => Stacking::Ignore,
+            StatusKind::Burn | StatusKind::Silence | StatusKind::Haste | StatusKind::Slow => Stacking::Refresh,
+        }
+    }
+
+    pub fn timing(self) -> TickTiming {
+        match self {
+            StatusKind::Bleed | StatusKind::Poison => TickTiming::StartOfTurn,
+            StatusKind::Burn => TickTiming::EndOfTurn,
+            StatusKind::Stun | StatusKind::Silence | StatusKind::Haste | StatusKind::Slow => TickTiming::Never,
+        }
+    }
+
+    /// The damage type of the ticks, for damage over time effects.
+    pub fn damage_type(self) -> Option<DamageType> {
+        match self {
+            StatusKind::Bleed => Some(DamageType::Slash),
+            StatusKind::Burn => Some(DamageType::Fire),
+            StatusKind::Poison => Some(DamageType::Nature),
+            _ => None,
+        }
+    }
+}
+
+/// Content files only say what to apply, like `(status: Bleed, turns: 3, potency: 2)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(from = "StatusDef")]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Turns of the owner left, counted down at the end of each of them.
+    pub remaining: u32,
+    pub stacks: u32,
+    /// Damage per stack per tick for damage over time, percent for haste and slow.
+    pub potency: i32,
+    pub source: Option<CombatantId>,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, turns: u32, potency: i32) -> Self {
+        StatusEffect { kind, remaining: turns, stacks: 1, potency, source: None }
+    }
+
+    pub fn by(mut self, source: CombatantId) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct StatusDef {
+    status: StatusKind,
+    turns: u32,
+    #[serde(default)]
+    potency: i32,
+}
+
+impl From<StatusDef> for StatusEffect {
+    fn from(def: StatusDef) -> Self {
+        StatusEffect::new(def.status, def.turns, def.potency)
+    }
+}
+
+/// Damage a status deals on one tick, before mitigation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusTick {
+    pub kind: StatusKind,
+    pub stacks: u32,
+    pub damage: Damage,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Statuses {
+    effects: Vec<StatusEffect>,
+    /// Put on by the owner during its own turn, so this turn does not count.
+    fresh: Vec<StatusKind>,
+}
+
+impl Statuses {
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    /// Applies `effect` following the stacking rule of its kind.
+    /// Returns the resulting number of stacks, or None if it had no effect.
+    pub fn apply(&mut self, effect: StatusEffect) -> Option<u32> {
+        let existing = match self.effects.iter_mut().find(|e| e.kind == effect.kind) {
+            Some(existing) => existing,
+            None => {
+                self.effects.push(effect);
+                return Some(effect.stacks);
+            }
+        };
+        match effect.kind.stacking() {
+            Stacking::Ignore => return None,
+            Stacking::Refresh => {
+                existing.potency = existing.potency.max(effect.potency);
+            }
+            Stacking::Stack { max } => {
+                existing.stacks = (existing.stacks + effect.stacks).min(max);
+                existing.potency = existing.potency.max(effect.potency);
+            }
+        }
+        existing.remaining = existing.remaining.max(effect.remaining);
+        existing.source = effect.source.or(existing.source);
+        Some(existing.stacks)
+    }
+
+    /// Like `apply`, for a status the owner puts on itself on its own turn.
+    pub fn apply_own(&mut self, effect: StatusEffect) -> Option<u32> {
+        let stacks = self.apply(effect);
+        if stacks.is_some() {
+            self.fresh.push(effect.kind);
+        }
+        stacks
+    }
+
+    /// Damage from every status that ticks at `timing`.
+    pub fn ticks(&self, timing: TickTiming) -> Vec<StatusTick> {
+        self.effects.iter()
+            .filter(|e| e.kind.timing() == timing)
+            .filter_map(|e| {
+                let damage_type = e.kind.damage_type()?;
+                Some(StatusTick {
+                    kind: e.kind,
+                    stacks: e.stacks,
+                    damage: Damage::new(damage_type, e.potency * e.stacks as i32),
+                })
+            })
+            .collect()
+    }
+
+    /// Counts down one turn and removes what ran out, returning those.
+    pub fn count_down(&mut self) -> Vec<StatusKind> {
+        let fresh = std::mem::take(&mut self.fresh);
+        for e in self.effects.iter_mut().filter(|e| !fresh.contains(&e.kind)) {
+            e.remaining = e.remaining.saturating_sub(1);
+        }
+        let expired = self.effects.iter().filter(|e| e.remaining == 0).map(|e| e.kind).collect();
+        self.effects.retain(|e| e.remaining > 0);
+        expired
+    }
+
+    /// Stat modifiers from buffs and debuffs, haste and slow for now.
+    pub fn modifiers(&self) -> Modifiers {
+        let haste = self.effects.iter()
+            .map(|e| match e.kind {
+                StatusKind::Haste => e.potency,
+                StatusKind::Slow => -e.potency,
+                _ => 0,
+            })
+            .sum();
+        Modifiers { haste, ..Modifiers::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remaining(statuses: &Statuses, kind: StatusKind) -> Option<u32> {
+        statuses.iter().find(|e| e.kind == kind).map(|e| e.remaining)
+    }
+
+    #[test]
+    fn refresh_keeps_the_longer_duration_and_stronger_potency() {
+        let mut statuses = Statuses::default();
+        assert_eq!(statuses.apply(StatusEffect::new(StatusKind::Burn, 3, 2)), Some(1));
+        assert_eq!(statuses.apply(StatusEffect::new(StatusKind::Burn, 1, 5)), Some(1));
+        let burn = statuses.iter().next().copied().unwrap();
+        assert_eq!((burn.remaining, burn.potency, burn.stacks), (3, 5, 1));
+    }
+
+    #[test]
+    fn stacks_add_up_to_the_max() {
+        let mut statuses = Statuses::default();
+        for expected in [1, 2, 3, 3].iter() {
+            assert_eq!(statuses.apply(StatusEffect::new(StatusKind::Poison, 2, 1)), Some(*expected));
+        }
+        assert_eq!(statuses.iter().count(), 1);
+    }
+
+    #[test]
+    fn ignored_status_stays_as_it_was() {
+        let mut statuses = Statuses::default();
+        statuses.apply(StatusEffect::new(StatusKind::Stun, 1, 0));
+        assert_eq!(statuses.apply(StatusEffect::new(StatusKind::Stun, 3, 0)), None);
+        assert_eq!(remaining(&statuses, StatusKind::Stun), Some(1));
+    }
+
+    #[test]
+    fn statuses_expire_when_their_turns_run_out() {
+        let mut statuses = Statuses::default();
+        statuses.apply(StatusEffect::new(StatusKind::Bleed, 1, 2));
+        statuses.apply(StatusEffect::new(StatusKind::Slow, 2, 30));
+        assert_eq!(statuses.count_down(), vec![StatusKind::Bleed]);
+        assert!(statuses.has(StatusKind::Slow));
+        assert_eq!(statuses.count_down(), vec![StatusKind::Slow]);
+        assert_eq!(statuses.iter().count(), 0);
+    }
+
+    #[test]
+    fn ticks_come_at_their_timing() {
+        let mut statuses = Statuses::default();
+        statuses.apply(StatusEffect::new(StatusKind::Bleed, 3, 2));
+        statuses.apply(StatusEffect::new(StatusKind::Bleed, 3, 2));
+        statuses.apply(StatusEffect::new(StatusKind::Burn, 3, 4));
+        statuses.apply(StatusEffect::new(StatusKind::Haste, 3, 50));
+        let start = statuses.ticks(TickTiming::StartOfTurn);
+        assert_eq!(start.len(), 1);
+        assert_eq!((start[0].kind, start[0].stacks), (StatusKind::Bleed, 2));
+        assert_eq!(start[0].damage, Damage::new(DamageType::Slash, 4));
+        let end = statuses.ticks(TickTiming::EndOfTurn);
+        assert_eq!(end.len(), 1);
+        assert_eq!(end[0].damage, Damage::new(DamageType::Fire, 4));
+    }
+
+    #[test]
+    fn haste_and_slow_change_speed() {
+        let mut statuses = Statuses::default();
+        statuses.apply(StatusEffect::new(StatusKind::Haste, 3, 50));
+        statuses.apply(StatusEffect::new(StatusKind::Slow, 3, 20));
+        assert_eq!(statuses.modifiers().haste, 30);
+    }
+
+    #[test]
+    fn own_status_is_not_counted_down_the_turn_it_starts() {
+        let mut statuses = Statuses::default();
+        statuses.apply_own(StatusEffect::new(StatusKind::Haste, 2, 50));
+        assert!(statuses.count_down().is_empty());
+        assert_eq!(remaining(&statuses, StatusKind::Haste), Some(2));
+        assert!(statuses.count_down().is_empty());
+        assert_eq!(statuses.count_down(), vec![StatusKind::Haste]);
+        assert!(!statuses.has(StatusKind::Haste));
+    }
+
+    #[test]
+    fn status_from_others_is_counted_down_right_away() {
+        let mut statuses = Statuses::default();
+        statuses.apply(StatusEffect::new(StatusKind::Slow, 2, 30));
+        statuses.apply_own(StatusEffect::new(StatusKind::Haste, 2, 50));
+        statuses.count_down();
+        assert_eq!(remaining(&statuses, StatusKind::Slow), Some(1));
+        assert_eq!(remaining(&statuses, StatusKind::Haste), Some(2));
+    }
+}
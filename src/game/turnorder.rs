@@ -0,0 +1,39 @@
+use super::combat::Phase;
+use super::stats::Stat;
+use super::world::{Combatant, CombatantId, World};
+
+/// Higher acts first. Haste and slow scale the DEX based value.
+pub fn initiative(c: &Combatant) -> i32 {
+    c.stat(Stat::Initiative)
+}
+
+/// Everyone alive, in the order they act this round.
+/// Ties are broken by id so the order is stable.
+pub fn round_order(world: &World) -> Vec<CombatantId> {
+    let mut living: Vec<&Combatant> = world.combatants().iter().filter(|c| c.is_alive()).collect();
+    living.sort_by_key(|c| (-initiative(c), c.id));
+    living.iter().map(|c| c.id).collect()
+}
+
+/// The next `count` actors, starting with whoever is acting right now,
+/// assuming initiatives stay what they are now.
+pub fn project(world: &World, count: usize) -> Vec<CombatantId> {
+    let mut projection = Vec::with_capacity(count);
+    match world.combat.phase {
+        Phase::AwaitingInput(id) | Phase::EnemyTurn(id) => projection.push(id),
+        Phase::Victory | Phase::Defeat | Phase::Escaped => return projection,
+        _ => {}
+    }
+    let alive = |id: &CombatantId| world.combatant(*id).is_some_and(|c| c.is_alive());
+    projection.extend(world.combat.actors_left.iter().copied().filter(alive));
+
+    let round = round_order(world);
+    if round.is_empty() {
+        return projection;
+    }
+    while projection.len() < count {
+        projection.extend(round.iter().copied());
+    }
+    projection.truncate(count);
+    projection
+}
@@ -1 +1,451 @@
-pub struct World;
\ No newline at end of file
+use serde::Deserialize;
+
+use super::ability::{Ability, Cooldowns};
+use super::ai::AiProfile;
+use super::combat::Combat;
+use super::content::{Content, Item, NewCharacter};
+use super::event::CombatEvent;
+use super::damage::Resistances;
+use super::dungeon::{Dungeon, Pos};
+use super::explore::Mode;
+use super::equipment::{EquipSlot, Equipment};
+use super::inventory::{carry_capacity, Inventory, InventoryError, INVENTORY_SLOTS};
+use super::level::LevelCurve;
+use super::rng::Rng;
+use super::stance::Stance;
+use super::stats::Stat;
+use super::status::Statuses;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CombatantId(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
+
+/// Missing attributes default to 0 in content files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Attributes {
+    pub strength: i32,
+    pub dexterity: i32,
+    pub constitution: i32,
+    pub intelligence: i32,
+    pub wisdom: i32,
+}
+
+impl std::ops::Add for Attributes {
+    type Output = Attributes;
+
+    fn add(self, other: Attributes) -> Attributes {
+        Attributes {
+            strength: self.strength + other.strength,
+            dexterity: self.dexterity + other.dexterity,
+            constitution: self.constitution + other.constitution,
+            intelligence: self.intelligence + other.intelligence,
+            wisdom: self.wisdom + other.wisdom,
+        }
+    }
+}
+
+/// A resource that is spent and regained, like HP, MP or Stamina.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pool {
+    pub current: i32,
+    pub max: i32,
+}
+
+impl Pool {
+    pub fn full(max: i32) -> Self {
+        Pool { current: max, max }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current <= 0
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.max <= 0 {
+            0.0
+        } else {
+            self.current.max(0) as f32 / self.max as f32
+        }
+    }
+
+    /// Removes up to `amount`, returns how much was actually removed.
+    pub fn drain(&mut self, amount: i32) -> i32 {
+        let drained = amount.min(self.current).max(0);
+        self.current -= drained;
+        drained
+    }
+
+    /// Adds up to `amount` without going over max, returns how much was actually added.
+    pub fn restore(&mut self, amount: i32) -> i32 {
+        let restored = amount.min(self.max - self.current).max(0);
+        self.current += restored;
+        restored
+    }
+
+    /// Changes max, current gains or loses as much, so taking gear off and on again heals nothing.
+    /// Losing max never takes the last point, it stays within `0..=max` all the same.
+    pub fn set_max(&mut self, max: i32) {
+        let floor = self.current.min(1);
+        self.current = (self.current + max - self.max).max(floor).min(max);
+        self.max = max;
+    }
+}
+
+/// Max HP, MP and Stamina that go with `attributes`.
+fn pool_maxima(attributes: &Attributes) -> (i32, i32, i32) {
+    (
+        50 + attributes.constitution * 10,
+        attributes.intelligence * 10 + attributes.wisdom * 5,
+        50 + attributes.strength * 3 + attributes.constitution * 2,
+    )
+}
+
+pub struct Combatant {
+    pub id: CombatantId,
+    pub name: String,
+    pub class: String,
+    pub race: String,
+    pub faction: Faction,
+    /// Base attributes, before gear and buffs. See `stats` for the effective values.
+    pub attributes: Attributes,
+    pub level: u32,
+    pub xp: u32,
+    /// Left to spend on attributes, from leveling up.
+    pub attribute_points: u32,
+    /// Left to spend on abilities from `ability_pool`, from leveling up.
+    pub ability_picks: u32,
+    /// Abilities that can be learned when leveling up.
+    pub ability_pool: Vec<String>,
+    /// Added to the base attributes on every level up.
+    pub growth: Attributes,
+    /// Handed to the other side when this combatant dies.
+    pub xp_value: u32,
+    pub hp: Pool,
+    pub mp: Pool,
+    pub stamina: Pool,
+    /// Innate resistances.
+    pub resistances: Resistances,
+    /// Innate flat amount soaked from physical damage on every hit.
+    pub block: i32,
+    pub statuses: Statuses,
+    pub stance: Stance,
+    /// Blocking, until the start of their next turn.
+    pub guarding: bool,
+    /// Names of the abilities this combatant can use, in ability bar order.
+    pub abilities: Vec<String>,
+    pub cooldowns: Cooldowns,
+    /// How the game plays this combatant, None for the player.
+    pub ai: Option<AiProfile>,
+    pub inventory: Inventory,
+    pub equipment: Equipment,
+}
+
+impl Combatant {
+    /// Creates a combatant with full pools derived from its attributes.
+    /// The id is assigned when the combatant is added to a `World`.
+    pub fn new(name: &str, class: &str, race: &str, faction: Faction, attributes: Attributes) -> Self {
+        let (hp, mp, stamina) = pool_maxima(&attributes);
+        Combatant {
+            id: CombatantId(0),
+            name: name.to_string(),
+            class: class.to_string(),
+            race: race.to_string(),
+            faction,
+            attributes,
+            level: 1,
+            xp: 0,
+            attribute_points: 0,
+            ability_picks: 0,
+            ability_pool: Vec::new(),
+            growth: Attributes::default(),
+            xp_value: 0,
+            hp: Pool::full(hp),
+            mp: Pool::full(mp),
+            stamina: Pool::full(stamina),
+            resistances: Resistances::default(),
+            block: 0,
+            statuses: Statuses::default(),
+            stance: Stance::default(),
+            guarding: false,
+            abilities: Vec::new(),
+            cooldowns: Cooldowns::default(),
+            ai: None,
+            inventory: Inventory::new(INVENTORY_SLOTS, carry_capacity(attributes.strength)),
+            equipment: Equipment::default(),
+        }
+    }
+
+    pub fn with_resistances(mut self, resistances: Resistances) -> Self {
+        self.resistances = resistances;
+        self
+    }
+
+    pub fn with_block(mut self, block: i32) -> Self {
+        self.block = block;
+        self
+    }
+
+    pub fn with_abilities(mut self, abilities: &[&str]) -> Self {
+        self.abilities = abilities.iter().map(|a| a.to_string()).collect();
+        self
+    }
+
+    /// Brings the pool maxima and the carrying capacity in line with the effective
+    /// attributes, after they or the gear changed. A shrinking pool takes current down with it.
+    /// Going over the capacity is allowed, nothing more can be picked up until then.
+    pub fn grow_pools(&mut self) {
+        let attributes = self.effective_attributes();
+        let (hp, mp, stamina) = pool_maxima(&attributes);
+        self.hp.set_max(hp);
+        self.mp.set_max(mp);
+        self.stamina.set_max(stamina);
+        self.inventory.max_weight = carry_capacity(attributes.strength);
+    }
+
+    pub fn is_alive(&self) -> bool {
+        !self.hp.is_empty()
+    }
+
+    pub fn is_player(&self) -> bool {
+        self.faction == Faction::Player
+    }
+}
+
+pub struct World {
+    pub mode: Mode,
+    pub combat: Combat,
+    pub rng: Rng,
+    pub dungeon: Dungeon,
+    /// Where the player is in the dungeon.
+    pub position: Pos,
+    /// Where the player was before the last step, to back off to after fleeing.
+    pub previous_position: Pos,
+    pub abilities: Vec<Ability>,
+    pub items: Vec<Item>,
+    pub levels: LevelCurve,
+    combatants: Vec<Combatant>,
+    /// Combatants that fled or died, kept so the log can still name them.
+    departed: Vec<Combatant>,
+    /// Enemies of encounters the player got away from, as they were, by encounter index.
+    parked: Vec<(usize, Combatant)>,
+    next_id: u32,
+    events: Vec<CombatEvent>,
+}
+
+impl World {
+    pub fn new(seed: u64) -> Self {
+        World {
+            mode: Mode::Exploring,
+            combat: Combat::new(),
+            rng: Rng::new(seed),
+            dungeon: Dungeon::default(),
+            position: Pos::default(),
+            previous_position: Pos::default(),
+            abilities: Vec::new(),
+            items: Vec::new(),
+            levels: LevelCurve::default(),
+            combatants: Vec::new(),
+            departed: Vec::new(),
+            parked: Vec::new(),
+            next_id: 1,
+            events: Vec::new(),
+        }
+    }
+
+    /// `hero` at the start of a dungeon generated from `seed`, built from `content`.
+    pub fn new_game(seed: u64, content: &Content, hero: &NewCharacter) -> Self {
+        let mut world = World::new(seed);
+        world.dungeon = Dungeon::generate(seed, content);
+        world.position = world.dungeon.start;
+        world.previous_position = world.dungeon.start;
+        world.abilities = content.abilities.clone();
+        world.items = content.items.clone();
+        world.levels = content.levels.clone();
+        if let Some(c) = content.character(&hero.name, &hero.class, &hero.race, Faction::Player) {
+            world.spawn(c);
+        }
+        world
+    }
+
+    /// Picks up new content in the middle of a fight, see `Content::refresh`.
+    pub fn reload_content(&mut self, content: &Content) {
+        self.abilities = content.abilities.clone();
+        self.items = content.items.clone();
+        self.levels = content.levels.clone();
+        for c in self.combatants.iter_mut().chain(self.parked.iter_mut().map(|(_, c)| c)) {
+            content.refresh(c);
+        }
+    }
+
+    pub fn spawn(&mut self, mut combatant: Combatant) -> CombatantId {
+        let id = CombatantId(self.next_id);
+        self.next_id += 1;
+        combatant.id = id;
+        self.combatants.push(combatant);
+        id
+    }
+
+    pub fn combatants(&self) -> &[Combatant] {
+        &self.combatants
+    }
+
+    pub fn combatants_mut(&mut self) -> &mut [Combatant] {
+        &mut self.combatants
+    }
+
+    pub fn combatant(&self, id: CombatantId) -> Option<&Combatant> {
+        self.combatants.iter().find(|c| c.id == id)
+    }
+
+    pub fn combatant_mut(&mut self, id: CombatantId) -> Option<&mut Combatant> {
+        self.combatants.iter_mut().find(|c| c.id == id)
+    }
+
+    pub fn ability(&self, name: &str) -> Option<&Ability> {
+        self.abilities.iter().find(|a| a.name == name)
+    }
+
+    pub fn item(&self, name: &str) -> Option<&Item> {
+        self.items.iter().find(|i| i.name == name)
+    }
+
+    pub fn name_of(&self, id: CombatantId) -> &str {
+        self.combatants.iter()
+            .chain(&self.departed)
+            .chain(self.parked.iter().map(|(_, c)| c))
+            .find(|c| c.id == id)
+            .map(|c| c.name.as_str())
+            .unwrap_or("?")
+    }
+
+    /// Takes `id` out of the fight for good, without a reward for anyone.
+    pub fn leave_fight(&mut self, id: CombatantId) {
+        if let Some(index) = self.combatants.iter().position(|c| c.id == id) {
+            let c = self.combatants.remove(index);
+            self.departed.push(c);
+        }
+    }
+
+    /// Takes `id` out of the fight, to wait at `encounter` until the player comes back.
+    pub fn park(&mut self, id: CombatantId, encounter: usize) {
+        if let Some(index) = self.combatants.iter().position(|c| c.id == id) {
+            let c = self.combatants.remove(index);
+            self.parked.push((encounter, c));
+        }
+    }
+
+    /// Brings back whoever waits at `encounter`, keeping their ids.
+    pub fn unpark(&mut self, encounter: usize) -> Vec<CombatantId> {
+        let (back, parked): (Vec<_>, Vec<_>) = std::mem::take(&mut self.parked)
+            .into_iter()
+            .partition(|(e, _)| *e == encounter);
+        self.parked = parked;
+        let ids = back.iter().map(|(_, c)| c.id).collect();
+        self.combatants.extend(back.into_iter().map(|(_, c)| c));
+        ids
+    }
+
+    pub fn player(&self) -> Option<&Combatant> {
+        self.combatants.iter().find(|c| c.is_player())
+    }
+
+    pub fn player_mut(&mut self) -> Option<&mut Combatant> {
+        self.combatants.iter_mut().find(|c| c.is_player())
+    }
+
+    /// Rearranges the player's inventory. This is free and can be done at any time.
+    pub fn move_player_item(&mut self, from: usize, to: usize) {
+        let stack = self.player()
+            .and_then(|p| p.inventory.get(from))
+            .and_then(|s| self.item(&s.item))
+            .map_or(1, |i| i.stack);
+        if let Some(player) = self.player_mut() {
+            player.inventory.move_slot(from, to, stack);
+        }
+    }
+
+    /// Wears the gear in inventory `slot` of the player. Free, like moving items around,
+    /// but only while exploring.
+    pub fn equip_player_item(&mut self, slot: usize) -> Result<(), InventoryError> {
+        if self.mode != Mode::Exploring {
+            return Err(InventoryError::InCombat);
+        }
+        let item = match self.player().and_then(|p| p.inventory.get(slot)).and_then(|s| self.item(&s.item)) {
+            Some(item) => item.clone(),
+            None => return Ok(()),
+        };
+        match self.player_mut() {
+            Some(player) => player.equip(slot, &item),
+            None => Ok(()),
+        }
+    }
+
+    pub fn unequip_player(&mut self, slot: EquipSlot) -> Result<(), InventoryError> {
+        if self.mode != Mode::Exploring {
+            return Err(InventoryError::InCombat);
+        }
+        match self.player_mut() {
+            Some(player) => player.unequip(slot),
+            None => Ok(()),
+        }
+    }
+
+    /// Spends one of the player's attribute points on `stat`.
+    pub fn raise_player_attribute(&mut self, stat: Stat) {
+        if let Some(player) = self.player_mut() {
+            player.raise_attribute(stat);
+        }
+    }
+
+    /// Spends one of the player's ability picks on `ability`.
+    pub fn learn_player_ability(&mut self, ability: &str) {
+        if let Some(player) = self.player_mut() {
+            player.learn(ability);
+        }
+    }
+
+    /// Shares the XP of a dead combatant among the living on the other side.
+    pub fn award_xp(&mut self, dead: CombatantId) {
+        let (faction, amount) = match self.combatant(dead) {
+            Some(c) if c.xp_value > 0 => (c.faction, c.xp_value),
+            _ => return,
+        };
+        let winners: Vec<CombatantId> = self.combatants.iter()
+            .filter(|c| c.is_alive() && c.faction != faction)
+            .map(|c| c.id)
+            .collect();
+        if winners.is_empty() {
+            return;
+        }
+        let share = (amount / winners.len() as u32).max(1);
+        for id in winners {
+            let levels = match self.combatants.iter_mut().find(|c| c.id == id) {
+                Some(c) => c.gain_xp(share, &self.levels),
+                None => continue,
+            };
+            self.emit(CombatEvent::XpGained { target: id, amount: share });
+            for level in levels {
+                self.emit(CombatEvent::LeveledUp { target: id, level });
+            }
+        }
+    }
+
+    pub fn enemies(&self) -> impl Iterator<Item = &Combatant> {
+        self.combatants.iter().filter(|c| c.faction == Faction::Enemy)
+    }
+
+    pub fn emit(&mut self, event: CombatEvent) {
+        self.events.push(event);
+    }
+
+    /// Hands over every event emitted since the last call.
+    pub fn drain_events(&mut self) -> Vec<CombatEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
@@ -19,7 +19,7 @@ pub struct EguiMq<'a> {
 
 impl EguiMq<'_> {
     pub fn new() -> Self {
-        let mut mq_ctx = {
+        let mq_ctx = {
             let macroquad::prelude::InternalGlContext {
                 quad_context: ctx, ..
             } = unsafe { macroquad::prelude::get_internal_gl() };
@@ -29,7 +29,7 @@ impl EguiMq<'_> {
 
         Self {
             egui_ctx: egui::CtxRef::default(),
-            painter: painter::Painter::new(&mut mq_ctx),
+            painter: painter::Painter::new(mq_ctx),
             egui_input: Default::default(),
             mq_ctx,
         }
@@ -87,8 +87,8 @@ impl EguiMq<'_> {
 
         let (x, y) = inp::mouse_position();
         self.egui_input.mouse_pos = Some(egui::pos2(
-            x as f32 / dpi_scale,
-            y as f32 / dpi_scale,
+            x / dpi_scale,
+            y / dpi_scale,
         ));
 
         if inp::is_mouse_button_down(inp::MouseButton::Left) {
@@ -1,5 +1,6 @@
 pub use self::lib::*;
 pub use self::input::*;
+#[allow(unused_imports)]
 pub use self::painter::*;
 
 pub mod input;
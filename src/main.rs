@@ -1,6 +1,12 @@
+use std::path::Path;
+
+use game::content::{Content, ContentWatcher, NewCharacter};
+use game::explore::Mode;
 use guiintegration::{EguiMq, UiDrawer};
+use ui::creation::CharacterCreation;
+use ui::LevelUpChoice;
+use ui::inventory::InventoryCommand;
 use macroquad::prelude::{next_frame};
-use ui::UiState;
 
 mod guiintegration;
 mod ui;
@@ -8,26 +14,130 @@ mod game;
 
 struct UiDrawerCurringWorld<'a> {
     w: &'a game::World,
-    ui_state: &'a mut ui::draw::UiState,
+    ui_state: &'a mut ui::UiState,
 }
 
 impl<'a> UiDrawer for UiDrawerCurringWorld<'a> {
     fn draw_ui(&mut self, egui_ctx: &mut egui::CtxRef) {
-        ui::draw::draw_ui(egui_ctx, &self.w, &mut self.ui_state);
+        ui::draw_ui(egui_ctx, self.w, self.ui_state);
+    }
+}
+
+struct UiDrawerExploration<'a> {
+    w: &'a game::World,
+    ui_state: &'a mut ui::UiState,
+}
+
+impl<'a> UiDrawer for UiDrawerExploration<'a> {
+    fn draw_ui(&mut self, egui_ctx: &mut egui::CtxRef) {
+        ui::draw_exploration(egui_ctx, self.w, self.ui_state);
+    }
+}
+
+struct UiDrawerCreation<'a> {
+    content: &'a Content,
+    creation: &'a mut CharacterCreation,
+}
+
+impl<'a> UiDrawer for UiDrawerCreation<'a> {
+    fn draw_ui(&mut self, egui_ctx: &mut egui::CtxRef) {
+        ui::creation::draw_creation(egui_ctx, self.content, self.creation);
     }
 }
 
 #[macroquad::main("dungeon_fantasy")]
 async fn main() {
     let mut egui_mq = EguiMq::new();
-    let mut world = game::World{};
-    let mut ui_state = ui::draw::UiState{eventlog_entries: vec!["You take 10 physacal damage from bleed".to_string()]};
+    let content_dir = Path::new(game::content::CONTENT_DIR);
+    let mut content = match Content::load(content_dir) {
+        Ok(content) => content,
+        Err(errors) => {
+            for e in errors {
+                eprintln!("{}", e);
+            }
+            std::process::exit(1);
+        }
+    };
+    let mut hero = NewCharacter { name: "Erik".to_string(), class: "Rogue".to_string(), race: "Human".to_string() };
+    // The world is built once a character has been made.
+    let mut creation = Some(CharacterCreation::new(hero.clone()));
+    let mut world = game::World::new(time_seed());
+    let mut ui_state = ui::UiState::new();
+    let mut watcher = ContentWatcher::new(content_dir);
 
     loop {
-        game::gameloop::gameloop(&mut world);
+        if watcher.poll() {
+            match Content::load(content_dir) {
+                Ok(reloaded) => {
+                    world.reload_content(&reloaded);
+                    content = reloaded;
+                    ui_state.content_errors.clear();
+                }
+                Err(errors) => ui_state.content_errors = errors.iter().map(|e| e.to_string()).collect(),
+            }
+        }
+        if let Some(state) = creation.as_mut() {
+            egui_mq.update(&mut UiDrawerCreation { content: &content, creation: state });
+            if let Some(chosen) = state.chosen.take() {
+                hero = chosen;
+                world = game::World::new_game(time_seed(), &content, &hero);
+                ui_state = ui::UiState::new();
+                creation = None;
+            }
+            next_frame().await;
+            continue;
+        }
 
-        egui_mq.update(&mut UiDrawerCurringWorld{w: &world, ui_state: &mut ui_state});
+        game::gameloop(&mut world);
+        ui_state.eventlog_entries.extend(world.drain_events());
+        if world.combat.awaiting_input().is_none() {
+            ui_state.intent_error = None;
+        }
+
+        match world.mode {
+            Mode::Exploring => egui_mq.update(&mut UiDrawerExploration{w: &world, ui_state: &mut ui_state}),
+            Mode::Combat { .. } => egui_mq.update(&mut UiDrawerCurringWorld{w: &world, ui_state: &mut ui_state}),
+        }
+
+        for intent in ui_state.intents.drain(..) {
+            ui_state.intent_error = world.submit_intent(intent).err();
+        }
+        for direction in ui_state.moves.drain(..) {
+            world.move_player(direction, &content);
+        }
+        for command in std::mem::take(&mut ui_state.inventory.commands) {
+            let result = match command {
+                InventoryCommand::Move { from, to } => {
+                    world.move_player_item(from, to);
+                    Ok(())
+                }
+                InventoryCommand::Equip { slot } => world.equip_player_item(slot),
+                InventoryCommand::Unequip { slot } => world.unequip_player(slot),
+            };
+            ui_state.inventory.error = result.err();
+        }
+        for choice in ui_state.level_up.drain(..) {
+            match choice {
+                LevelUpChoice::Raise(stat) => world.raise_player_attribute(stat),
+                LevelUpChoice::Learn(ability) => world.learn_player_ability(&ability),
+            }
+        }
+        if let Some(seed) = ui_state.restart_with_seed.take() {
+            world = game::World::new_game(seed, &content, &hero);
+            ui_state.eventlog_entries.clear();
+            ui_state.bars = ui::bars::ResourceBars::new();
+        }
+        if std::mem::take(&mut ui_state.new_character) {
+            creation = Some(CharacterCreation::new(hero.clone()));
+        }
 
         next_frame().await;
     }
 }
+
+fn time_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use egui::{Align2, Color32, Rect, Sense, Stroke, TextStyle, Ui};
+
+use crate::game::event::CombatEvent;
+use crate::game::world::{Combatant, CombatantId, Pool};
+
+/// Seconds the ghost segment stays put before it starts catching up.
+const GHOST_DELAY: f64 = 0.4;
+/// Seconds it takes the ghost segment to catch up once it moves.
+const GHOST_DURATION: f64 = 0.6;
+const FLASH_DURATION: f64 = 0.5;
+const BAR_HEIGHT: f32 = 16.0;
+
+const HP_COLOR: Color32 = Color32::from_rgb(170, 30, 30);
+const MP_COLOR: Color32 = Color32::from_rgb(30, 60, 190);
+const STAMINA_COLOR: Color32 = Color32::from_rgb(190, 160, 30);
+const LOST_COLOR: Color32 = Color32::from_rgb(230, 230, 200);
+const GAINED_COLOR: Color32 = Color32::from_rgb(140, 230, 140);
+const FLASH_COLOR: Color32 = Color32::from_rgb(255, 240, 80);
+const EMPTY_COLOR: Color32 = Color32::from_rgb(30, 30, 30);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Resource {
+    Hp,
+    Mp,
+    Stamina,
+}
+
+impl Resource {
+    fn label(self) -> &'static str {
+        match self {
+            Resource::Hp => "HP",
+            Resource::Mp => "MP",
+            Resource::Stamina => "STAMINA",
+        }
+    }
+
+    fn color(self) -> Color32 {
+        match self {
+            Resource::Hp => HP_COLOR,
+            Resource::Mp => MP_COLOR,
+            Resource::Stamina => STAMINA_COLOR,
+        }
+    }
+
+    fn pool(self, c: &Combatant) -> Pool {
+        match self {
+            Resource::Hp => c.hp,
+            Resource::Mp => c.mp,
+            Resource::Stamina => c.stamina,
+        }
+    }
+}
+
+/// Animation state of one bar, kept between frames.
+struct BarAnimation {
+    /// The pool as the bar shows it right now.
+    pool: Pool,
+    /// Where the ghost segment started moving from at the last change.
+    ghost_from: f32,
+    changed_at: f64,
+    flash_until: f64,
+}
+
+impl BarAnimation {
+    fn new(pool: Pool) -> Self {
+        BarAnimation { pool, ghost_from: pool.fraction(), changed_at: f64::NEG_INFINITY, flash_until: f64::NEG_INFINITY }
+    }
+
+    /// The fraction the bar is filled to right now.
+    fn value(&self) -> f32 {
+        self.pool.fraction()
+    }
+
+    /// The trailing end of the bar, which lags behind `value`.
+    fn ghost(&self, now: f64) -> f32 {
+        let t = ((now - self.changed_at - GHOST_DELAY) / GHOST_DURATION).clamp(0.0, 1.0) as f32;
+        self.ghost_from + (self.value() - self.ghost_from) * t
+    }
+
+    /// Damage or healing from an event, the ghost trails behind it.
+    fn change(&mut self, amount: i32, now: f64) {
+        self.ghost_from = self.ghost(now);
+        self.pool.current = (self.pool.current + amount).clamp(0, self.pool.max);
+        self.changed_at = now;
+    }
+
+    /// Catches up with changes no event told us about, like spent mana or a level up, without animating.
+    fn sync(&mut self, pool: Pool) {
+        if pool != self.pool {
+            self.pool = pool;
+            self.ghost_from = pool.fraction();
+        }
+    }
+}
+
+/// HP/MP/Stamina bars for every combatant, with the animation state they need.
+#[derive(Default)]
+pub struct ResourceBars {
+    bars: HashMap<(CombatantId, Resource), BarAnimation>,
+    /// How many combat events we have looked at.
+    seen_events: usize,
+}
+
+impl ResourceBars {
+    pub fn new() -> Self {
+        ResourceBars::default()
+    }
+
+    /// Looks at events the bars have not seen yet, to animate damage and healing and flash on crits.
+    /// Bars that were never drawn start out from the pool when they are.
+    pub fn process_events(&mut self, events: &[CombatEvent], now: f64) {
+        if events.len() < self.seen_events {
+            self.seen_events = 0;
+        }
+        for event in &events[self.seen_events..] {
+            match event {
+                CombatEvent::Damaged { target, crit, result, .. } => {
+                    if let Some(bar) = self.bars.get_mut(&(*target, Resource::Hp)) {
+                        bar.change(-result.taken(), now);
+                        if *crit {
+                            bar.flash_until = now + FLASH_DURATION;
+                        }
+                    }
+                }
+                CombatEvent::Healed { target, amount, .. } => {
+                    if let Some(bar) = self.bars.get_mut(&(*target, Resource::Hp)) {
+                        bar.change(*amount, now);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.seen_events = events.len();
+    }
+
+    pub fn draw(&mut self, ui: &mut Ui, c: &Combatant) {
+        for resource in [Resource::Hp, Resource::Mp, Resource::Stamina].iter() {
+            self.draw_bar(ui, c, *resource);
+        }
+    }
+
+    fn draw_bar(&mut self, ui: &mut Ui, c: &Combatant, resource: Resource) {
+        let now = ui.input().time;
+        let pool = resource.pool(c);
+        let bar = self.bars.entry((c.id, resource)).or_insert_with(|| BarAnimation::new(pool));
+        bar.sync(pool);
+
+        let width = ui.available_width();
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(width, BAR_HEIGHT), Sense::hover());
+        let painter = ui.painter();
+        let span = |from: f32, to: f32| {
+            Rect::from_min_max(
+                egui::pos2(rect.left() + rect.width() * from, rect.top()),
+                egui::pos2(rect.left() + rect.width() * to, rect.bottom()),
+            )
+        };
+        painter.rect_filled(rect, 2.0, EMPTY_COLOR);
+        let (value, ghost) = (bar.value(), bar.ghost(now));
+        if ghost > value {
+            painter.rect_filled(span(value, ghost), 0.0, LOST_COLOR);
+            painter.rect_filled(span(0.0, value), 2.0, resource.color());
+        } else {
+            painter.rect_filled(span(ghost, value), 0.0, GAINED_COLOR);
+            painter.rect_filled(span(0.0, ghost), 2.0, resource.color());
+        }
+        if now < bar.flash_until {
+            // Pulse a few times while the flash lasts.
+            let pulse = ((bar.flash_until - now) * 20.0).sin().abs() as f32;
+            painter.rect_stroke(rect, 2.0, Stroke::new(1.0 + 2.0 * pulse, FLASH_COLOR));
+        }
+        painter.text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            format!("{} {}/{}", resource.label(), pool.current, pool.max),
+            TextStyle::Small,
+            Color32::WHITE,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::damage::{DamageResult, DamageType, PartResult};
+    use crate::game::event::DamageCause;
+
+    const TARGET: CombatantId = CombatantId(1);
+
+    fn hit(amount: i32, crit: bool) -> CombatEvent {
+        let part = PartResult { damage_type: DamageType::Pierce, raw: amount, resisted: 0, blocked: 0 };
+        CombatEvent::Damaged {
+            source: None,
+            target: TARGET,
+            cause: DamageCause::Ability("Stab".to_string()),
+            crit,
+            result: DamageResult { parts: vec![part] },
+        }
+    }
+
+    fn bars(current: i32) -> ResourceBars {
+        let mut bars = ResourceBars::new();
+        bars.bars.insert((TARGET, Resource::Hp), BarAnimation::new(Pool { current, max: 100 }));
+        bars
+    }
+
+    fn hp(bars: &ResourceBars) -> &BarAnimation {
+        &bars.bars[&(TARGET, Resource::Hp)]
+    }
+
+    #[test]
+    fn damage_leaves_a_ghost_that_catches_up() {
+        let mut bars = bars(100);
+        bars.process_events(&[hit(40, false)], 10.0);
+        assert_eq!(hp(&bars).value(), 0.6);
+        assert_eq!(hp(&bars).ghost(10.0), 1.0);
+        assert_eq!(hp(&bars).ghost(10.0 + GHOST_DELAY + GHOST_DURATION), 0.6);
+        assert!(hp(&bars).flash_until < 10.0);
+    }
+
+    #[test]
+    fn healing_and_crits_come_from_events() {
+        let mut bars = bars(50);
+        let events = [
+            hit(10, true),
+            CombatEvent::Healed { source: TARGET, target: TARGET, ability: "Mend".to_string(), amount: 30 },
+        ];
+        bars.process_events(&events, 10.0);
+        assert_eq!(hp(&bars).pool.current, 70);
+        assert!(hp(&bars).flash_until > 10.0);
+        // Already seen, nothing happens twice.
+        bars.process_events(&events, 11.0);
+        assert_eq!(hp(&bars).pool.current, 70);
+    }
+
+    #[test]
+    fn changes_without_events_do_not_animate() {
+        let mut bars = bars(100);
+        bars.bars.get_mut(&(TARGET, Resource::Hp)).unwrap().sync(Pool { current: 20, max: 100 });
+        assert_eq!(hp(&bars).ghost(0.0), 0.2);
+    }
+}
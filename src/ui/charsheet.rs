@@ -0,0 +1,115 @@
+use egui::Ui;
+
+use crate::game::attack::AttackCategory;
+use crate::game::damage::{DamageType, Resistances};
+use crate::game::level::LevelCurve;
+use crate::game::stats::{Breakdown, Modifiers, Stat};
+use crate::game::world::Combatant;
+
+fn breakdown_text(b: &Breakdown, unit: &str) -> String {
+    format!("Base {}{}\nGear {:+}{}\nBuffs and stance {:+}{}", b.base, unit, b.gear, unit, b.buffs, unit)
+}
+
+/// One line per stat that `m` changes, like "+2 STR".
+pub fn modifier_lines(m: &Modifiers) -> Vec<String> {
+    // Each value with what goes after it, "%" first for percentages.
+    let mut stats: Vec<(i32, String)> = Vec::new();
+    let attributes = [m.attributes.strength, m.attributes.dexterity, m.attributes.constitution,
+        m.attributes.intelligence, m.attributes.wisdom];
+    for (stat, value) in Stat::ATTRIBUTES.iter().zip(attributes.iter()) {
+        stats.push((*value, format!(" {}", stat.name())));
+    }
+    stats.push((m.ratings.spellpower, " Spellpower".to_string()));
+    for category in AttackCategory::ALL.iter() {
+        stats.push((m.ratings.rating(*category).hit, format!("% {}", Stat::Hit(*category).name())));
+        stats.push((m.ratings.rating(*category).crit, format!("% {}", Stat::Crit(*category).name())));
+    }
+    stats.push((m.ratings.evasion, " Evasion".to_string()));
+    stats.push((m.ratings.willpower, " Willpower".to_string()));
+    for damage_type in DamageType::ALL.iter() {
+        stats.push((m.resistances.get(*damage_type), format!("% {} resistance", damage_type)));
+    }
+    stats.push((m.block, " Block".to_string()));
+    stats.push((m.haste, "% Haste".to_string()));
+    stats.into_iter()
+        .filter(|(value, _)| *value != 0)
+        .map(|(value, label)| format!("{:+}{}", value, label))
+        .collect()
+}
+
+/// One `name value` row of a grid, with the breakdown as tooltip.
+fn stat_row(ui: &mut Ui, c: &Combatant, stat: Stat, unit: &str) {
+    let b = c.breakdown(stat);
+    let mut hint = breakdown_text(&b, unit);
+    if let Stat::Resistance(damage_type) = stat {
+        hint.push_str(&format!("\nCapped at {}% to {}%", Resistances::MIN, Resistances::MAX));
+        let uncapped = c.modifiers().resistances.get(damage_type);
+        if uncapped != b.total() {
+            hint.push_str(&format!(", {}% before the cap", uncapped));
+        }
+    }
+    ui.label(stat.name());
+    ui.label(format!("{}{}", b.total(), unit)).on_hover_text(hint);
+    ui.end_row();
+}
+
+pub fn draw_character_sheet(ui: &mut Ui, c: &Combatant, levels: &LevelCurve) {
+    egui::Grid::new("charsheet_identity").show(ui, |ui| {
+        ui.label("Name:");
+        ui.label(&c.name);
+        ui.end_row();
+        ui.label("Class:");
+        ui.label(&c.class);
+        ui.end_row();
+        ui.label("Race:");
+        ui.label(&c.race);
+        ui.end_row();
+        ui.label("Level:");
+        ui.label(c.level.to_string());
+        ui.end_row();
+        ui.label("XP:");
+        match levels.xp_for(c.level + 1) {
+            Some(next) => ui.label(format!("{}/{}", c.xp, next)),
+            None => ui.label(format!("{} (max level)", c.xp)),
+        };
+        ui.end_row();
+    });
+    ui.separator();
+
+    egui::Grid::new("charsheet_attributes").show(ui, |ui| {
+        for stat in Stat::ATTRIBUTES.iter() {
+            stat_row(ui, c, *stat, "");
+        }
+    });
+    ui.separator();
+
+    egui::Grid::new("charsheet_ratings").show(ui, |ui| {
+        stat_row(ui, c, Stat::Spellpower, "");
+        for category in AttackCategory::ALL.iter() {
+            stat_row(ui, c, Stat::Hit(*category), "%");
+            stat_row(ui, c, Stat::Crit(*category), "%");
+        }
+        stat_row(ui, c, Stat::Initiative, "");
+        stat_row(ui, c, Stat::Block, "");
+    });
+    ui.separator();
+
+    ui.label("Resistances");
+    egui::Grid::new("charsheet_resistances").show(ui, |ui| {
+        for damage_type in DamageType::ALL.iter() {
+            stat_row(ui, c, Stat::Resistance(*damage_type), "%");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changed_stats_are_listed() {
+        let m: Modifiers = ron::de::from_str("(attributes: (strength: 2), block: 3, haste: -5)").unwrap();
+        assert_eq!(modifier_lines(&m), vec!["+2 STR", "+3 Block", "-5% Haste"]);
+        assert!(modifier_lines(&Modifiers::default()).is_empty());
+    }
+}
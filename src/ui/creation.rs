@@ -0,0 +1,133 @@
+use egui::Ui;
+
+use crate::game::content::{Content, NewCharacter};
+use crate::game::damage::DamageType;
+use crate::game::stats::Stat;
+use crate::game::world::{Attributes, Combatant, Faction};
+
+use super::eventlog::{ITEM_COLOR, SPELL_COLOR, YOU_COLOR};
+
+/// Choices on the character creation screen, shown before the game starts.
+pub struct CharacterCreation {
+    pub hero: NewCharacter,
+    /// Set when the player is done, the main loop starts the game with it.
+    pub chosen: Option<NewCharacter>,
+}
+
+impl CharacterCreation {
+    /// Starts from `hero`, like the last character played.
+    pub fn new(hero: NewCharacter) -> Self {
+        CharacterCreation { hero, chosen: None }
+    }
+}
+
+pub fn draw_creation(ctx: &mut egui::CtxRef, content: &Content, state: &mut CharacterCreation) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.heading("NEW CHARACTER");
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut state.hero.name);
+        });
+        ui.separator();
+
+        let hero = &mut state.hero;
+        ui.columns(3, |columns| {
+            columns[0].label("CLASS");
+            for class in content.classes.iter().filter(|c| c.playable) {
+                let response = columns[0].selectable_label(hero.class == class.name, &class.name);
+                if response.on_hover_text(&class.description).clicked {
+                    hero.class = class.name.clone();
+                }
+            }
+            columns[1].label("RACE");
+            for race in content.races.iter().filter(|r| r.playable) {
+                let response = columns[1].selectable_label(hero.race == race.name, &race.name);
+                if response.on_hover_text(&race.description).clicked {
+                    hero.race = race.name.clone();
+                }
+            }
+            match content.character(&hero.name, &hero.class, &hero.race, Faction::Player) {
+                Some(c) => draw_preview(&mut columns[2], content, &c),
+                None => {
+                    columns[2].label("Pick a class and a race.");
+                }
+            }
+        });
+        ui.separator();
+
+        let ready = !state.hero.name.trim().is_empty()
+            && content.class(&state.hero.class).is_some_and(|c| c.playable)
+            && content.race(&state.hero.race).is_some_and(|r| r.playable);
+        if ui.add(egui::Button::new("Start").enabled(ready)).clicked {
+            state.hero.name = state.hero.name.trim().to_string();
+            state.chosen = Some(state.hero.clone());
+        }
+    });
+}
+
+/// The level 1 character the current choices make.
+fn draw_preview(ui: &mut Ui, content: &Content, c: &Combatant) {
+    ui.colored_label(YOU_COLOR, format!("{} the {} {}", c.name, c.race, c.class));
+    for description in content.class(&c.class).into_iter().map(|cl| &cl.description)
+        .chain(content.race(&c.race).map(|r| &r.description))
+        .filter(|d| !d.is_empty())
+    {
+        ui.label(description);
+    }
+    ui.separator();
+
+    egui::Grid::new("creation_attributes").show(ui, |ui| {
+        for (stat, value, growth) in attribute_rows(&c.attributes, &c.growth) {
+            ui.label(stat.name());
+            ui.label(value.to_string());
+            ui.small(format!("{:+} per level", growth));
+            ui.end_row();
+        }
+    });
+    ui.label(format!("HP {}  MP {}  Stamina {}", c.hp.max, c.mp.max, c.stamina.max));
+    let resistances: Vec<String> = DamageType::ALL.iter()
+        .filter(|t| c.resistances.get(**t) != 0)
+        .map(|t| format!("{} {:+}%", t, c.resistances.get(*t)))
+        .collect();
+    if !resistances.is_empty() {
+        ui.label(format!("Resistances: {}", resistances.join(", ")));
+    }
+    ui.separator();
+
+    ui.label("Abilities");
+    ui.horizontal_wrapped(|ui| {
+        for ability in &c.abilities {
+            ui.colored_label(SPELL_COLOR, ability);
+        }
+    });
+    if !c.ability_pool.is_empty() {
+        ui.label("Learnable later");
+        ui.horizontal_wrapped(|ui| {
+            for ability in &c.ability_pool {
+                ui.colored_label(SPELL_COLOR, ability);
+            }
+        });
+    }
+    let gear: Vec<&str> = c.equipment.iter().map(|i| i.name.as_str()).collect();
+    let items: Vec<String> = c.inventory.slots().iter().flatten()
+        .map(|s| if s.count > 1 { format!("{} x{}", s.item, s.count) } else { s.item.clone() })
+        .collect();
+    if !gear.is_empty() || !items.is_empty() {
+        ui.label("Starting gear");
+        ui.horizontal_wrapped(|ui| {
+            for item in gear.iter().map(|g| g.to_string()).chain(items) {
+                ui.colored_label(ITEM_COLOR, item);
+            }
+        });
+    }
+}
+
+fn attribute_rows(attributes: &Attributes, growth: &Attributes) -> Vec<(Stat, i32, i32)> {
+    vec![
+        (Stat::Strength, attributes.strength, growth.strength),
+        (Stat::Dexterity, attributes.dexterity, growth.dexterity),
+        (Stat::Constitution, attributes.constitution, growth.constitution),
+        (Stat::Intelligence, attributes.intelligence, growth.intelligence),
+        (Stat::Wisdom, attributes.wisdom, growth.wisdom),
+    ]
+}
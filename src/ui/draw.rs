@@ -1,55 +1,567 @@
-use std::string;
+use egui::Ui;
 
-use egui::{Color32, Label, TextStyle, Ui};
+use crate::game::ability::{Ability, Targeting};
+use crate::game::attack::AttackCategory;
+use crate::game::combat::{Phase, PlayerIntent};
+use crate::game::damage::DamageType;
+use crate::game::event::{CombatEvent, FailReason};
+use crate::game::explore::Direction;
+use crate::game::flee::flee_chance;
+use crate::game::stance::{guard_modifiers, Stance};
+use crate::game::stats::Stat;
+use crate::game::turnorder;
+use crate::game::world::{Combatant, CombatantId, World};
 
-use crate::game::world::World;
+use super::bars::ResourceBars;
+use super::charsheet;
+use super::layout::{self, region, CombatLayout, ExploreLayout};
+use super::map;
+use super::eventlog::{self, EventLogView, ENEMY_COLOR, YOU_COLOR};
+use super::inventory::{self, InventoryView};
 
 pub struct UiState {
-    pub eventlog_entries: Vec<String>,
+    pub eventlog_entries: Vec<CombatEvent>,
+    pub eventlog_view: EventLogView,
+    pub bars: ResourceBars,
+    pub inventory: InventoryView,
+    /// Enemy the ability bar acts on. Falls back to the first living enemy.
+    pub selected_target: Option<CombatantId>,
+    /// Filled by the UI, drained into `game::combat::Combat` by the main loop.
+    pub intents: Vec<PlayerIntent>,
+    /// Why the last intent was turned down, set by the main loop.
+    pub intent_error: Option<FailReason>,
+    /// Steps asked for while exploring, made in order by the main loop.
+    pub moves: Vec<Direction>,
+    /// Points spent in the level up window, applied to the player by the main loop.
+    pub level_up: Vec<LevelUpChoice>,
+    pub seed_input: String,
+    /// Set when the user asks to start over, the main loop rebuilds the world from it.
+    pub restart_with_seed: Option<u64>,
+    /// Set when the user asks for another character, the main loop goes back to character creation.
+    pub new_character: bool,
+    /// Problems from the last content reload. The game keeps the content it had.
+    pub content_errors: Vec<String>,
 }
 
-pub fn draw_ui(ctx: &mut egui::CtxRef, _w: &World, state: &mut UiState) {
-    let mut show_egui_demo_windows: bool = true;
+impl UiState {
+    pub fn new() -> Self {
+        UiState {
+            eventlog_entries: Vec::new(),
+            eventlog_view: EventLogView::new(),
+            bars: ResourceBars::new(),
+            inventory: InventoryView::new(),
+            selected_target: None,
+            intents: Vec::new(),
+            intent_error: None,
+            moves: Vec::new(),
+            level_up: Vec::new(),
+            seed_input: String::new(),
+            restart_with_seed: None,
+            new_character: false,
+            content_errors: Vec::new(),
+        }
+    }
+}
 
-    egui::Window::new("Debug").show(ctx, |ui| {
-        ui.add(egui::Label::new("Egui on Macroquad").text_style(egui::TextStyle::Heading));
-        ui.separator();
-        ui.checkbox(&mut show_egui_demo_windows, "Show egui demo windows");
-        ui.label("Woooohoooo!");
-        if ui.button("Quit").clicked {
-            std::process::exit(0);
+pub fn draw_ui(ctx: &mut egui::CtxRef, w: &World, state: &mut UiState) {
+    draw_sheet_panel(ctx, w);
+
+    state.bars.process_events(&state.eventlog_entries, ctx.input().time);
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let l = CombatLayout::split(ui.max_rect());
+        let player = w.player();
+        let target = current_target(w, state).and_then(|id| w.combatant(id));
+
+        region(ui, l.portrait, |ui| {
+            if let Some(player) = player {
+                draw_figure(ui, &player.name, YOU_COLOR);
+            }
+        });
+        region(ui, l.player_bars, |ui| {
+            if let Some(player) = player {
+                state.bars.draw(ui, player);
+            }
+            if let Some(reason) = state.intent_error {
+                ui.colored_label(egui::Color32::RED, format!("Can't do that: {}", eventlog::fail_reason(reason)));
+            }
+        });
+        region(ui, l.turn_order, |ui| {
+            ui.label("TURN ORDER");
+            draw_turn_order(ui, w);
+        });
+        region(ui, l.scene, |ui| draw_scene(ui, w));
+        region(ui, l.log, |ui| {
+            eventlog::draw_event_log(ui, w, &state.eventlog_entries, &mut state.eventlog_view);
+        });
+        region(ui, l.map, |ui| {
+            ui.label("MAP");
+            map::draw_map(ui, &w.dungeon, w.position);
+        });
+        region(ui, l.enemy_art, |ui| {
+            draw_target_picker(ui, w, state);
+            if let Some(target) = target {
+                draw_figure(ui, &target.name, ENEMY_COLOR);
+            }
+        });
+        region(ui, l.enemy_bars, |ui| {
+            if let Some(target) = target {
+                state.bars.draw(ui, target);
+            }
+        });
+        draw_action_grid(ui, l.actions, w, state);
+    });
+
+    if w.combat.phase == Phase::Defeat {
+        let screen = ctx.input().screen_rect();
+        egui::Window::new("Game Over").default_pos(screen.center()).show(ctx, |ui| {
+            draw_game_over(ui, w, state);
+        });
+    }
+
+    draw_windows(ctx, w, state);
+}
+
+/// The way out of a lost fight, the same dungeon again or another character.
+fn draw_game_over(ui: &mut Ui, w: &World, state: &mut UiState) {
+    ui.heading("DEFEAT");
+    if let Some(player) = w.player() {
+        ui.label(format!("{} fell on level {}.", player.name, player.level));
+    }
+    ui.horizontal(|ui| {
+        if ui.button("Try again").clicked {
+            state.restart_with_seed = Some(w.rng.seed());
+        }
+        if ui.button("New character").clicked {
+            state.new_character = true;
+        }
+    });
+}
+
+/// The dungeon between fights: the map to walk around on and what happened so far.
+pub fn draw_exploration(ctx: &mut egui::CtxRef, w: &World, state: &mut UiState) {
+    draw_sheet_panel(ctx, w);
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let l = ExploreLayout::split(ui.max_rect());
+        region(ui, l.map, |ui| {
+            ui.label("MAP");
+            map::draw_map(ui, &w.dungeon, w.position);
+        });
+        region(ui, l.log, |ui| {
+            eventlog::draw_event_log(ui, w, &state.eventlog_entries, &mut state.eventlog_view);
+        });
+        region(ui, l.controls, |ui| draw_move_controls(ui, state));
+    });
+
+    if !ctx.wants_keyboard_input() {
+        let input = ctx.input();
+        let keys = [
+            (egui::Key::ArrowUp, Direction::North),
+            (egui::Key::ArrowDown, Direction::South),
+            (egui::Key::ArrowRight, Direction::East),
+            (egui::Key::ArrowLeft, Direction::West),
+        ];
+        state.moves.extend(keys.iter().filter(|(key, _)| input.key_pressed(*key)).map(|(_, d)| *d));
+    }
+
+    draw_windows(ctx, w, state);
+}
+
+fn draw_move_controls(ui: &mut Ui, state: &mut UiState) {
+    ui.label("MOVE");
+    let mut button = |ui: &mut Ui, label: &str, direction: Direction| {
+        if ui.button(label).clicked {
+            state.moves.push(direction);
+        }
+    };
+    egui::Grid::new("move_controls").show(ui, |ui| {
+        ui.label("");
+        button(ui, "N", Direction::North);
+        ui.end_row();
+        button(ui, "W", Direction::West);
+        ui.label("");
+        button(ui, "E", Direction::East);
+        ui.end_row();
+        ui.label("");
+        button(ui, "S", Direction::South);
+        ui.end_row();
+    });
+    ui.small("or the arrow keys");
+    ui.separator();
+    if ui.selectable_label(state.inventory.open, "Inventory").clicked {
+        state.inventory.open = !state.inventory.open;
+    }
+}
+
+fn draw_sheet_panel(ctx: &mut egui::CtxRef, w: &World) {
+    let sheet_width = ctx.input().screen_rect().width() * layout::SHEET_WIDTH;
+    egui::SidePanel::left("character_sheet", sheet_width).show(ctx, |ui| {
+        ui.set_min_width(sheet_width);
+        if let Some(player) = w.player() {
+            egui::ScrollArea::auto_sized().id_source("character_sheet_scroll").show(ui, |ui| {
+                charsheet::draw_character_sheet(ui, player, &w.levels);
+            });
         }
     });
+}
 
-    egui::Window::new("Event Log").show(ctx, |ui| {
-        populate_event_log(ui);
+/// Inventory, level up and debug, the same in every mode.
+fn draw_windows(ctx: &mut egui::CtxRef, w: &World, state: &mut UiState) {
+    let screen = ctx.input().screen_rect();
+    let mut inventory_open = state.inventory.open;
+    egui::Window::new("Inventory").open(&mut inventory_open).show(ctx, |ui| {
+        if let Some(player) = w.player() {
+            let enemy = current_target(w, state);
+            inventory::draw_inventory(ui, w, player, enemy, &mut state.inventory, &mut state.intents);
+        }
+    });
+    state.inventory.open = inventory_open;
+
+    if let Some(player) = w.player().filter(|p| has_level_up(p)) {
+        egui::Window::new("Level Up").default_pos(screen.center()).show(ctx, |ui| {
+            draw_level_up(ui, w, player, state);
+        });
+    }
+
+    egui::Window::new("Debug").default_pos(screen.center()).show(ctx, |ui| {
+        draw_debug(ui, w, state);
+    });
+}
+
+fn draw_debug(ui: &mut Ui, w: &World, state: &mut UiState) {
+    ui.label(format!("Seed: {}", w.rng.seed()));
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut state.seed_input);
+        let parsed = state.seed_input.trim().parse::<u64>();
+        if ui.add(egui::Button::new("Replay seed").enabled(parsed.is_ok())).clicked {
+            state.restart_with_seed = parsed.ok();
+        }
+        if ui.button("Restart").clicked {
+            state.restart_with_seed = Some(w.rng.seed());
+        }
+        if ui.button("New character").clicked {
+            state.new_character = true;
+        }
     });
+    if !state.content_errors.is_empty() {
+        ui.colored_label(egui::Color32::RED, "Content failed to reload:");
+        for error in &state.content_errors {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+    ui.label(format!("Turn {} - {:?}", w.combat.turn, w.combat.phase));
+    if w.combat.awaiting_input().is_some() && ui.button("Skip turn").clicked {
+        state.intents.push(PlayerIntent::Wait);
+    }
+    ui.separator();
+    if let Some(player) = w.player() {
+        combatant_summary(ui, player);
+    }
+    ui.separator();
+    for enemy in w.enemies() {
+        combatant_summary(ui, enemy);
+    }
+    if ui.button("Quit").clicked {
+        std::process::exit(0);
+    }
+}
+
+/// What the player does with a level up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LevelUpChoice {
+    Raise(Stat),
+    Learn(String),
+}
+
+/// Whether `c` has anything left to spend from leveling up.
+fn has_level_up(c: &Combatant) -> bool {
+    c.attribute_points > 0 || (c.ability_picks > 0 && c.learnable().next().is_some())
+}
+
+fn draw_level_up(ui: &mut Ui, w: &World, player: &Combatant, state: &mut UiState) {
+    ui.heading(format!("Level {}", player.level));
+    if player.attribute_points > 0 {
+        ui.label(format!("{} attribute points to spend", player.attribute_points));
+        egui::Grid::new("level_up_attributes").show(ui, |ui| {
+            for stat in Stat::ATTRIBUTES.iter() {
+                ui.label(stat.name());
+                ui.label(player.stat(*stat).to_string());
+                if ui.small_button("+").clicked {
+                    state.level_up.push(LevelUpChoice::Raise(*stat));
+                }
+                ui.end_row();
+            }
+        });
+    }
+    let learnable: Vec<&Ability> = player.learnable().filter_map(|name| w.ability(name)).collect();
+    if player.ability_picks > 0 && !learnable.is_empty() {
+        ui.separator();
+        ui.label(format!("{} new abilities to pick", player.ability_picks));
+        for ability in learnable {
+            let hint = ability_button(player, ability, None).hint;
+            let mut response = ui.button(&ability.name);
+            if !hint.is_empty() {
+                response = response.on_hover_text(hint.join("\n"));
+            }
+            if response.clicked {
+                state.level_up.push(LevelUpChoice::Learn(ability.name.clone()));
+            }
+        }
+    }
+}
 
-    egui::Area::new("Whatever")
-        .fixed_pos(egui::pos2(32.0, 32.0))
-        .show(ctx, |ui| {
-            for spell in &["Stab", "Claw", "Fireball", "Escape"] {
-                if ui.button(spell.to_string()).clicked {
-                    println!("clicked the spell {}", spell)
+/// One cell of the action grid.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ActionSlot {
+    Attack,
+    /// Index into the player's known abilities.
+    Ability(usize),
+    Block,
+    Stance,
+    Inventory,
+    Escape,
+    Empty,
+}
+
+const ACTION_GRID_COLUMNS: usize = 6;
+const ACTION_GRID_ROWS: usize = 2;
+const ACTION_GRID: [ActionSlot; ACTION_GRID_COLUMNS * ACTION_GRID_ROWS] = [
+    ActionSlot::Attack,
+    ActionSlot::Ability(0),
+    ActionSlot::Ability(1),
+    ActionSlot::Block,
+    ActionSlot::Inventory,
+    ActionSlot::Stance,
+    ActionSlot::Ability(2),
+    ActionSlot::Ability(3),
+    ActionSlot::Ability(4),
+    ActionSlot::Ability(5),
+    ActionSlot::Empty,
+    ActionSlot::Escape,
+];
+
+fn draw_action_grid(ui: &mut Ui, area: egui::Rect, w: &World, state: &mut UiState) {
+    let target = current_target(w, state);
+    let player = w.player();
+    let my_turn = w.combat.awaiting_input().is_some();
+    let cells = layout::grid_cells(area, ACTION_GRID_COLUMNS, ACTION_GRID_ROWS);
+    for (slot, cell) in ACTION_GRID.iter().zip(cells) {
+        let button = match *slot {
+            ActionSlot::Attack => Some(SlotButton::new("Attack", target.map(|target| PlayerIntent::Attack { target }))),
+            ActionSlot::Ability(index) => player
+                .and_then(|p| p.abilities.get(index).and_then(|name| w.ability(name)).map(|a| (p, a)))
+                .map(|(p, ability)| ability_button(p, ability, target)),
+            ActionSlot::Escape => player.map(|p| {
+                let mut button = SlotButton::new("Escape", Some(PlayerIntent::Escape));
+                button.hint.push(format!("{}% chance to get away", flee_chance(w, p.id)));
+                button
+            }),
+            ActionSlot::Block => {
+                let mut button = SlotButton::new("Block", Some(PlayerIntent::Block));
+                button.hint = charsheet::modifier_lines(&guard_modifiers());
+                button.hint.push("until your next turn".to_string());
+                Some(button)
+            }
+            // Not single buttons, drawn below.
+            ActionSlot::Stance | ActionSlot::Inventory => None,
+            ActionSlot::Empty => None,
+        };
+        region(ui, cell, |ui| {
+            if *slot == ActionSlot::Inventory {
+                if ui.selectable_label(state.inventory.open, "Inventory").clicked {
+                    state.inventory.open = !state.inventory.open;
                 }
+                return;
+            }
+            if *slot == ActionSlot::Stance {
+                if let Some(player) = player {
+                    draw_stance_picker(ui, player, my_turn, &mut state.intents);
+                }
+                return;
+            }
+            let button = match button {
+                Some(button) => button,
+                None => return,
+            };
+            let enabled = my_turn && button.intent.is_some() && button.why_not.is_none();
+            let mut hover = button.hint;
+            if let Some(why_not) = button.why_not.filter(|_| my_turn) {
+                hover.push(why_not);
+            }
+            let mut response = ui.add(egui::Button::new(button.label).enabled(enabled));
+            if !hover.is_empty() {
+                response = response.on_hover_text(hover.join("\n"));
+            }
+            if response.clicked {
+                state.intents.extend(button.intent);
             }
         });
+    }
+}
+
+/// The stances to switch to, switching takes the turn.
+fn draw_stance_picker(ui: &mut Ui, player: &Combatant, my_turn: bool, intents: &mut Vec<PlayerIntent>) {
+    ui.label("Stance");
+    ui.horizontal_wrapped(|ui| {
+        for stance in Stance::ALL.iter() {
+            let current = player.stance == *stance;
+            let mut hint = charsheet::modifier_lines(&stance.modifiers());
+            if hint.is_empty() {
+                hint.push("No bonuses or penalties".to_string());
+            }
+            let response = ui.selectable_label(current, stance.name()).on_hover_text(hint.join("\n"));
+            if response.clicked && my_turn && !current {
+                intents.push(PlayerIntent::ChangeStance { stance: *stance });
+            }
+        }
+    });
+}
+
+/// What one cell of the action grid shows and does.
+struct SlotButton {
+    label: String,
+    intent: Option<PlayerIntent>,
+    /// Shown on hover, like costs.
+    hint: Vec<String>,
+    /// Why the button is disabled, if something other than the turn stops it.
+    why_not: Option<String>,
+}
+
+impl SlotButton {
+    fn new(label: &str, intent: Option<PlayerIntent>) -> Self {
+        SlotButton { label: label.to_string(), intent, hint: Vec::new(), why_not: None }
+    }
+}
+
+fn ability_button(player: &Combatant, ability: &Ability, enemy: Option<CombatantId>) -> SlotButton {
+    let target = match ability.targeting {
+        Targeting::Enemy | Targeting::AllEnemies => enemy,
+        Targeting::User | Targeting::Ally => Some(player.id),
+    };
+    let cooldown = player.cooldowns.remaining(&ability.name);
+    let label = if cooldown > 0 { format!("{} ({})", ability.name, cooldown) } else { ability.name.clone() };
+    let mut hint = Vec::new();
+    if ability.mp_cost > 0 {
+        hint.push(format!("{} MP", ability.mp_cost));
+    }
+    if ability.stamina_cost > 0 {
+        hint.push(format!("{} Stamina", ability.stamina_cost));
+    }
+    if ability.cooldown > 0 {
+        hint.push(format!("Cooldown {} turns", ability.cooldown));
+    }
+    let why_not = match (player.check_ability(ability), target) {
+        (Err(reason), _) => Some(eventlog::fail_reason(reason)),
+        (Ok(()), None) => Some(eventlog::fail_reason(FailReason::InvalidTarget)),
+        (Ok(()), Some(_)) => None,
+    };
+    SlotButton {
+        label,
+        intent: target.map(|target| PlayerIntent::UseAbility { name: ability.name.clone(), target }),
+        hint,
+        why_not,
+    }
+}
+
+fn draw_target_picker(ui: &mut Ui, w: &World, state: &mut UiState) {
+    let target = current_target(w, state);
+    ui.horizontal_wrapped(|ui| {
+        for enemy in w.enemies().filter(|e| e.is_alive()) {
+            if ui.selectable_label(Some(enemy.id) == target, &enemy.name).clicked {
+                state.selected_target = Some(enemy.id);
+            }
+        }
+    });
+}
+
+/// What is going on right now, above the log.
+fn draw_scene(ui: &mut Ui, w: &World) {
+    let status = match w.combat.phase {
+        Phase::Victory => "VICTORY".to_string(),
+        Phase::Defeat => "DEFEAT".to_string(),
+        Phase::Escaped => "ESCAPED".to_string(),
+        Phase::AwaitingInput(_) => "Your turn".to_string(),
+        Phase::EnemyTurn(id) => format!("{}'s turn", w.name_of(id)),
+        _ => String::new(),
+    };
+    ui.heading(format!("TURN {}", w.combat.turn));
+    ui.label(status);
+}
+
+/// Stick figure placeholder for portraits and enemy art.
+fn draw_figure(ui: &mut Ui, name: &str, color: egui::Color32) {
+    ui.colored_label(color, name);
+    let rect = ui.available_rect_before_wrap_finite();
+    let size = rect.width().min(rect.height()) * 0.8;
+    let c = rect.center();
+    let stroke = egui::Stroke::new(2.0, color);
+    let head = size * 0.12;
+    let neck = c + egui::vec2(0.0, -size * 0.5 + head * 2.0);
+    let hip = c + egui::vec2(0.0, size * 0.15);
+    let painter = ui.painter();
+    painter.circle_stroke(c + egui::vec2(0.0, -size * 0.5 + head), head, stroke);
+    painter.line_segment([neck, hip], stroke);
+    painter.line_segment([neck + egui::vec2(-size * 0.25, size * 0.15), neck + egui::vec2(size * 0.25, size * 0.15)], stroke);
+    painter.line_segment([hip, hip + egui::vec2(-size * 0.15, size * 0.3)], stroke);
+    painter.line_segment([hip, hip + egui::vec2(size * 0.15, size * 0.3)], stroke);
 }
 
-fn populate_event_log(ui: &mut Ui) {
-        "You cast Astro Blast on Scarecrow, it hits for 120 physical damage";
-        let spell_color = Color32::from_rgb(100, 150, 0);
-        let you_color = Color32::from_rgb(0, 50, 200);
-        let enemy_color = Color32::from_rgb(150, 50, 50);
-        let damage_color = Color32::from_rgb(200, 20, 20);
-        ui.horizontal_wrapped_for_text(TextStyle::Body, |ui| {
-            ui.colored_label(you_color, "You");
-            ui.label("cast");
-            ui.colored_label(spell_color, "Astro Blast");
-            ui.label("on");
-            ui.colored_label(enemy_color, "Scarecrow");
-            ui.label("it hits for");
-            ui.colored_label(damage_color, "120 physical");
-            ui.label("damage");
+/// The selected target if it is still a valid one, otherwise the first living enemy.
+fn current_target(w: &World, state: &UiState) -> Option<CombatantId> {
+    state.selected_target
+        .filter(|id| w.combatant(*id).is_some_and(|c| c.is_alive()))
+        .or_else(|| w.enemies().find(|e| e.is_alive()).map(|e| e.id))
+}
+
+const TURN_ORDER_PROJECTION: usize = 8;
+
+fn draw_turn_order(ui: &mut Ui, w: &World) {
+    for (i, id) in turnorder::project(w, TURN_ORDER_PROJECTION).into_iter().enumerate() {
+        let c = match w.combatant(id) {
+            Some(c) => c,
+            None => continue,
+        };
+        let (color, label) = if c.is_player() {
+            (YOU_COLOR, "PLAYER".to_string())
+        } else {
+            (ENEMY_COLOR, c.name.to_uppercase())
+        };
+        let label = if i == 0 { format!("> {}", label) } else { label };
+        ui.horizontal(|ui| {
+            ui.colored_label(color, label);
+            ui.small(format!("init {}", turnorder::initiative(c)));
         });
-}
\ No newline at end of file
+    }
+}
+
+fn combatant_summary(ui: &mut Ui, c: &Combatant) {
+    let name = if c.is_alive() { c.name.clone() } else { format!("{} (dead)", c.name) };
+    ui.label(format!("{} - {} {}", name, c.race, c.class));
+    ui.label(format!("HP {}/{} ({:.0}%)  MP {}/{}  STA {}/{}",
+        c.hp.current, c.hp.max, c.hp.fraction() * 100.0,
+        c.mp.current, c.mp.max, c.stamina.current, c.stamina.max));
+    let resistances: Vec<String> = DamageType::ALL.iter()
+        .filter(|t| c.resistances.get(**t) != 0)
+        .map(|t| format!("{} {}%", t, c.resistances.get(*t)))
+        .collect();
+    let statuses: Vec<String> = c.statuses.iter()
+        .map(|s| if s.stacks > 1 {
+            format!("{} x{} ({})", s.kind.name(), s.stacks, s.remaining)
+        } else {
+            format!("{} ({})", s.kind.name(), s.remaining)
+        })
+        .collect();
+    if !statuses.is_empty() {
+        ui.label(format!("Status: {}", statuses.join(", ")));
+    }
+    ui.label(format!("Stance: {}{}", c.stance.name(), if c.guarding { ", blocking" } else { "" }));
+    let ratings = c.ratings();
+    let hit_crit: Vec<String> = AttackCategory::ALL.iter()
+        .map(|cat| format!("{} {}/{}%", cat.name(), ratings.rating(*cat).hit, ratings.rating(*cat).crit))
+        .collect();
+    ui.label(format!("Hit/Crit: {}", hit_crit.join("  ")));
+    if !resistances.is_empty() || c.block > 0 {
+        ui.label(format!("Resist: {}  Block: {}", resistances.join(", "), c.block));
+    }
+}
@@ -0,0 +1,450 @@
+use egui::{Align, Color32, TextStyle, Ui};
+
+use crate::game::ai::DecisionReason;
+use crate::game::event::{CombatEvent, DamageCause, FailReason};
+use crate::game::damage::DamageResult;
+use crate::game::world::{CombatantId, World};
+
+pub const SPELL_COLOR: Color32 = Color32::from_rgb(100, 150, 0);
+pub const YOU_COLOR: Color32 = Color32::from_rgb(0, 50, 200);
+pub const ENEMY_COLOR: Color32 = Color32::from_rgb(150, 50, 50);
+pub const DAMAGE_COLOR: Color32 = Color32::from_rgb(200, 20, 20);
+pub const STATUS_COLOR: Color32 = Color32::from_rgb(150, 100, 200);
+pub const HEAL_COLOR: Color32 = Color32::from_rgb(40, 170, 60);
+pub const ITEM_COLOR: Color32 = Color32::from_rgb(190, 140, 20);
+pub const DECISION_COLOR: Color32 = Color32::from_rgb(120, 120, 120);
+pub const XP_COLOR: Color32 = Color32::from_rgb(220, 190, 60);
+
+/// A piece of a log line, colored if it names something.
+pub struct Segment {
+    pub text: String,
+    pub color: Option<Color32>,
+}
+
+fn plain(text: impl Into<String>) -> Segment {
+    Segment { text: text.into(), color: None }
+}
+
+fn colored(color: Color32, text: impl Into<String>) -> Segment {
+    Segment { text: text.into(), color: Some(color) }
+}
+
+fn is_you(w: &World, id: CombatantId) -> bool {
+    w.combatant(id).is_some_and(|c| c.is_player())
+}
+
+fn who(w: &World, id: CombatantId) -> Segment {
+    if is_you(w, id) {
+        colored(YOU_COLOR, "You")
+    } else {
+        colored(ENEMY_COLOR, w.name_of(id))
+    }
+}
+
+fn whose(w: &World, id: CombatantId) -> Segment {
+    if is_you(w, id) {
+        colored(YOU_COLOR, "Your")
+    } else {
+        colored(ENEMY_COLOR, format!("{}'s", w.name_of(id)))
+    }
+}
+
+/// Picks the verb form that goes with `who`.
+fn verb(w: &World, id: CombatantId, you: &str, them: &str) -> Segment {
+    plain(if is_you(w, id) { you } else { them })
+}
+
+fn damage_segments(result: &DamageResult) -> Vec<Segment> {
+    let types: Vec<&str> = result.parts.iter().map(|p| p.damage_type.name()).collect();
+    let mut segments = vec![
+        colored(DAMAGE_COLOR, format!("{} {}", result.taken(), types.join("/"))),
+        plain("damage"),
+    ];
+    let mut mitigated = vec![format!("{} raw", result.raw())];
+    if result.resisted() != 0 {
+        mitigated.push(format!("{} resisted", result.resisted()));
+    }
+    if result.blocked() != 0 {
+        mitigated.push(format!("{} blocked", result.blocked()));
+    }
+    if mitigated.len() > 1 {
+        segments.push(plain(format!("({})", mitigated.join(", "))));
+    }
+    segments
+}
+
+/// Why an action did nothing, as shown in the log and on disabled buttons.
+pub fn fail_reason(reason: FailReason) -> String {
+    match reason {
+        FailReason::UnknownAbility => "nobody knows how".to_string(),
+        FailReason::Silenced => "silenced".to_string(),
+        FailReason::OnCooldown(1) => "ready next turn".to_string(),
+        FailReason::OnCooldown(turns) => format!("ready in {} turns", turns),
+        FailReason::NotEnoughMana => "not enough mana".to_string(),
+        FailReason::NotEnoughStamina => "not enough stamina".to_string(),
+        FailReason::InvalidTarget => "no valid target".to_string(),
+        FailReason::NotUsable => "it can't be used".to_string(),
+        FailReason::NotYourTurn => "not your turn".to_string(),
+    }
+}
+
+/// Turns an event into the colored pieces of its log line.
+pub fn segments(w: &World, event: &CombatEvent) -> Vec<Segment> {
+    match event {
+        CombatEvent::Encountered { enemies } => {
+            let mut s = vec![colored(YOU_COLOR, "You"), plain("run into")];
+            for (i, enemy) in enemies.iter().enumerate() {
+                if i > 0 {
+                    s.push(plain(if i + 1 == enemies.len() { "and" } else { "," }));
+                }
+                s.push(who(w, *enemy));
+            }
+            s
+        }
+        CombatEvent::FoundItem { target, item, count, taken } => {
+            let mut s = vec![who(w, *target), verb(w, *target, "find", "finds"),
+                colored(ITEM_COLOR, if *count > 1 { format!("{} x{}", item, count) } else { item.clone() })];
+            if !taken {
+                s.push(plain(", but there is no room for it"));
+            }
+            s
+        }
+        CombatEvent::TurnStarted { turn } => vec![plain(format!("TURN {}", turn))],
+        CombatEvent::Decided { actor, profile, reason } => {
+            let mut s = vec![who(w, *actor), colored(DECISION_COLOR, format!("({})", profile.name()))];
+            let whom = |id: &CombatantId| if id == actor { plain("itself") } else { who(w, *id) };
+            match reason {
+                DecisionReason::StrongestAttack => s.push(plain("goes for its strongest attack")),
+                DecisionReason::CastingSpell => s.push(plain("has mana to spare")),
+                DecisionReason::ConservingMana => s.push(plain("saves its mana")),
+                DecisionReason::Supporting(ally) => s.extend(vec![plain("looks after"), whom(ally)]),
+                DecisionReason::Healing(ally) => s.extend(vec![plain("tends to"), whom(ally)]),
+                DecisionReason::Panicking => s.push(plain("panics")),
+                DecisionReason::NothingBetter => s.push(plain("lashes out")),
+            }
+            s
+        }
+        CombatEvent::AbilityUsed { actor, ability, target } => {
+            let mut s = if ability == "Attack" {
+                vec![who(w, *actor), verb(w, *actor, "attack", "attacks")]
+            } else {
+                vec![who(w, *actor), verb(w, *actor, "use", "uses"), colored(SPELL_COLOR, ability.as_str())]
+            };
+            if let Some(target) = target {
+                if ability != "Attack" {
+                    s.push(plain("on"));
+                }
+                s.push(who(w, *target));
+            }
+            s
+        }
+        CombatEvent::ActionFailed { actor, ability, reason } => {
+            vec![who(w, *actor), verb(w, *actor, "fail", "fails"), plain("to use"),
+                colored(SPELL_COLOR, ability.as_str()), plain(format!("({})", fail_reason(*reason)))]
+        }
+        CombatEvent::ItemUsed { actor, item, target } => {
+            let mut s = vec![who(w, *actor), verb(w, *actor, "use", "uses"), colored(ITEM_COLOR, item.as_str())];
+            if let Some(target) = target {
+                s.push(plain("on"));
+                s.push(who(w, *target));
+            }
+            s
+        }
+        CombatEvent::Missed { actor, target, ability } => {
+            vec![whose(w, *actor), colored(SPELL_COLOR, ability.as_str()), plain("misses"), who(w, *target)]
+        }
+        CombatEvent::Damaged { source, target, cause, crit, result } => {
+            let mut s = match (cause, source) {
+                (DamageCause::Ability(ability), Some(source)) => vec![
+                    whose(w, *source),
+                    colored(SPELL_COLOR, ability.as_str()),
+                    plain(if *crit { "CRITS" } else { "hits" }),
+                    who(w, *target),
+                    plain("for"),
+                ],
+                _ => vec![who(w, *target), verb(w, *target, "take", "takes")],
+            };
+            s.extend(damage_segments(result));
+            match cause {
+                DamageCause::Status(status) => {
+                    s.push(plain("from"));
+                    s.push(colored(STATUS_COLOR, status.name()));
+                }
+                DamageCause::Ability(_) => {}
+            }
+            s
+        }
+        CombatEvent::Healed { source, target, ability, amount } => vec![
+            whose(w, *source), colored(SPELL_COLOR, ability.as_str()), plain("heals"), who(w, *target),
+            plain("for"), colored(HEAL_COLOR, amount.to_string()),
+        ],
+        CombatEvent::StatusApplied { target, status, stacks } => {
+            let mut s = vec![who(w, *target), verb(w, *target, "are", "is"), plain("afflicted by"),
+                colored(STATUS_COLOR, status.name())];
+            if *stacks > 1 {
+                s.push(plain(format!("(x{})", stacks)));
+            }
+            s
+        }
+        CombatEvent::StatusExpired { target, status } => {
+            vec![colored(STATUS_COLOR, status.name()), plain("fades from"), who(w, *target)]
+        }
+        CombatEvent::TurnLost { actor, status } => vec![
+            who(w, *actor), verb(w, *actor, "lose", "loses"), plain("the turn to"), colored(STATUS_COLOR, status.name()),
+        ],
+        CombatEvent::Waited { actor } => vec![who(w, *actor), verb(w, *actor, "wait", "waits")],
+        CombatEvent::Guarded { actor } => vec![who(w, *actor), verb(w, *actor, "raise your guard", "raises its guard")],
+        CombatEvent::StanceChanged { actor, stance } => vec![
+            who(w, *actor), verb(w, *actor, "take", "takes"), plain("a"), colored(STATUS_COLOR, stance.name()), plain("stance"),
+        ],
+        CombatEvent::FleeFailed { actor, chance } => vec![
+            who(w, *actor), verb(w, *actor, "try", "tries"), plain("to escape, but"), verb(w, *actor, "are", "is"),
+            plain("cut off"), plain(format!("({}%)", chance)),
+        ],
+        CombatEvent::Fled { actor, chance } => vec![
+            who(w, *actor), verb(w, *actor, "get away", "flees the fight"), plain(format!("({}%)", chance)),
+        ],
+        CombatEvent::Died { target } => vec![who(w, *target), verb(w, *target, "die", "dies")],
+        CombatEvent::XpGained { target, amount } => vec![
+            who(w, *target), verb(w, *target, "gain", "gains"), colored(XP_COLOR, format!("{} XP", amount)),
+        ],
+        CombatEvent::LeveledUp { target, level } => vec![
+            who(w, *target), verb(w, *target, "reach", "reaches"), colored(XP_COLOR, format!("level {}", level)),
+        ],
+        CombatEvent::Victory => vec![colored(YOU_COLOR, "VICTORY")],
+        CombatEvent::Defeat => vec![colored(DAMAGE_COLOR, "DEFEAT")],
+        CombatEvent::Escaped => vec![colored(YOU_COLOR, "ESCAPED")],
+    }
+}
+
+pub fn draw_event(ui: &mut Ui, w: &World, event: &CombatEvent) {
+    ui.horizontal_wrapped_for_text(TextStyle::Body, |ui| {
+        for segment in segments(w, event) {
+            match segment.color {
+                Some(color) => ui.colored_label(color, segment.text),
+                None => ui.label(segment.text),
+            };
+        }
+    });
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    Damage,
+    Heal,
+    Miss,
+    Status,
+    Decision,
+    Other,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 6] = [
+        EventKind::Damage,
+        EventKind::Heal,
+        EventKind::Miss,
+        EventKind::Status,
+        EventKind::Decision,
+        EventKind::Other,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::Damage => "Damage",
+            EventKind::Heal => "Heals",
+            EventKind::Miss => "Misses",
+            EventKind::Status => "Status",
+            EventKind::Decision => "AI",
+            EventKind::Other => "Other",
+        }
+    }
+
+    pub fn of(event: &CombatEvent) -> EventKind {
+        match event {
+            CombatEvent::Damaged { .. } => EventKind::Damage,
+            CombatEvent::Healed { .. } => EventKind::Heal,
+            CombatEvent::Decided { .. } => EventKind::Decision,
+            CombatEvent::Missed { .. } => EventKind::Miss,
+            CombatEvent::StatusApplied { .. }
+            | CombatEvent::StatusExpired { .. }
+            | CombatEvent::TurnLost { .. }
+            | CombatEvent::Guarded { .. }
+            | CombatEvent::StanceChanged { .. } => EventKind::Status,
+            _ => EventKind::Other,
+        }
+    }
+}
+
+/// Who did it and who it was done to, as far as the filters are concerned.
+fn participants(event: &CombatEvent) -> (Option<CombatantId>, Option<CombatantId>) {
+    match event {
+        CombatEvent::AbilityUsed { actor, target, .. } => (Some(*actor), *target),
+        CombatEvent::Missed { actor, target, .. } => (Some(*actor), Some(*target)),
+        CombatEvent::ItemUsed { actor, target, .. } => (Some(*actor), *target),
+        CombatEvent::Damaged { source, target, .. } => (*source, Some(*target)),
+        CombatEvent::Healed { source, target, .. } => (Some(*source), Some(*target)),
+        CombatEvent::Decided { actor, .. } => (Some(*actor), None),
+        CombatEvent::ActionFailed { actor, .. }
+        | CombatEvent::Waited { actor }
+        | CombatEvent::Guarded { actor }
+        | CombatEvent::StanceChanged { actor, .. }
+        | CombatEvent::FleeFailed { actor, .. }
+        | CombatEvent::Fled { actor, .. } => (Some(*actor), None),
+        CombatEvent::TurnLost { actor, .. } => (None, Some(*actor)),
+        CombatEvent::StatusApplied { target, .. }
+        | CombatEvent::StatusExpired { target, .. }
+        | CombatEvent::Died { target }
+        | CombatEvent::XpGained { target, .. }
+        | CombatEvent::FoundItem { target, .. }
+        | CombatEvent::LeveledUp { target, .. } => (None, Some(*target)),
+        CombatEvent::Encountered { .. }
+        | CombatEvent::TurnStarted { .. }
+        | CombatEvent::Victory
+        | CombatEvent::Defeat
+        | CombatEvent::Escaped => (None, None),
+    }
+}
+
+/// Filter, search and scroll state of the event log window.
+pub struct EventLogView {
+    pub source: Option<CombatantId>,
+    pub target: Option<CombatantId>,
+    pub hidden_kinds: Vec<EventKind>,
+    pub search: String,
+    /// Number of entries the last time we drew, to know when to scroll down.
+    seen: usize,
+}
+
+impl EventLogView {
+    pub fn new() -> Self {
+        EventLogView {
+            source: None,
+            target: None,
+            hidden_kinds: Vec::new(),
+            search: String::new(),
+            seen: 0,
+        }
+    }
+
+    fn accepts(&self, w: &World, event: &CombatEvent) -> bool {
+        if self.hidden_kinds.contains(&EventKind::of(event)) {
+            return false;
+        }
+        let (source, target) = participants(event);
+        if self.source.is_some() && source != self.source {
+            return false;
+        }
+        if self.target.is_some() && target != self.target {
+            return false;
+        }
+        let search = self.search.trim().to_lowercase();
+        if search.is_empty() {
+            return true;
+        }
+        let text: Vec<String> = segments(w, event).into_iter().map(|s| s.text).collect();
+        text.join(" ").to_lowercase().contains(&search)
+    }
+}
+
+/// A stretch of the log under one header. Fights are counted from 1 as they come,
+/// since every fight counts its turns from 1 again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Section {
+    /// Walking around after `fight`, 0 before the first one.
+    Exploring { fight: u32 },
+    /// Turn 0 is running into the enemies, before the first turn started.
+    Turn { fight: u32, turn: u32 },
+}
+
+impl Section {
+    fn title(self) -> String {
+        match self {
+            Section::Exploring { .. } => "EXPLORING".to_string(),
+            Section::Turn { fight, turn: 0 } => format!("FIGHT {}", fight),
+            Section::Turn { turn, .. } => format!("TURN {}", turn),
+        }
+    }
+}
+
+/// Events split up by the fight and turn they happened in, and the exploring in between.
+fn by_section(events: &[CombatEvent]) -> Vec<(Section, Vec<&CombatEvent>)> {
+    let mut sections: Vec<(Section, Vec<&CombatEvent>)> = vec![(Section::Exploring { fight: 0 }, Vec::new())];
+    let mut fight = 0;
+    for event in events {
+        let after_fight = match event {
+            CombatEvent::Encountered { .. } => {
+                fight += 1;
+                sections.push((Section::Turn { fight, turn: 0 }, Vec::new()));
+                false
+            }
+            CombatEvent::TurnStarted { turn } => {
+                sections.push((Section::Turn { fight, turn: *turn }, Vec::new()));
+                continue;
+            }
+            CombatEvent::Victory | CombatEvent::Defeat | CombatEvent::Escaped => true,
+            _ => false,
+        };
+        sections.last_mut().expect("starts exploring").1.push(event);
+        if after_fight {
+            sections.push((Section::Exploring { fight }, Vec::new()));
+        }
+    }
+    sections
+}
+
+fn combatant_filter(ui: &mut Ui, w: &World, label: &str, selected: &mut Option<CombatantId>) {
+    let current = selected.map_or("Anyone".to_string(), |id| w.name_of(id).to_string());
+    egui::combo_box_with_label(ui, label, current, |ui| {
+        ui.selectable_value(selected, None, "Anyone");
+        for c in w.combatants() {
+            ui.selectable_value(selected, Some(c.id), &c.name);
+        }
+    });
+}
+
+pub fn draw_event_log(ui: &mut Ui, w: &World, events: &[CombatEvent], view: &mut EventLogView) {
+    ui.horizontal(|ui| {
+        ui.label("Search:");
+        ui.text_edit_singleline(&mut view.search);
+    });
+    ui.horizontal(|ui| {
+        for kind in EventKind::ALL.iter() {
+            let mut shown = !view.hidden_kinds.contains(kind);
+            if ui.checkbox(&mut shown, kind.name()).clicked {
+                view.hidden_kinds.retain(|k| k != kind);
+                if !shown {
+                    view.hidden_kinds.push(*kind);
+                }
+            }
+        }
+    });
+    ui.horizontal(|ui| {
+        combatant_filter(ui, w, "Source", &mut view.source);
+        combatant_filter(ui, w, "Target", &mut view.target);
+    });
+    ui.separator();
+
+    let sections = by_section(events);
+    let latest = sections.iter().rev().find(|(_, entries)| !entries.is_empty()).map(|(section, _)| *section);
+    let height = ui.available_size().y.max(50.0);
+    egui::ScrollArea::from_max_height(height).id_source("event_log_scroll").show(ui, |ui| {
+        for (section, entries) in sections {
+            let entries: Vec<&CombatEvent> = entries.into_iter().filter(|e| view.accepts(w, e)).collect();
+            if entries.is_empty() {
+                continue;
+            }
+            egui::CollapsingHeader::new(section.title())
+                .id_source(("event_log_section", section))
+                .default_open(Some(section) == latest)
+                .show(ui, |ui| {
+                    for event in entries {
+                        draw_event(ui, w, event);
+                    }
+                });
+        }
+        if events.len() != view.seen {
+            view.seen = events.len();
+            ui.scroll_to_cursor(Align::Max);
+        }
+    });
+}
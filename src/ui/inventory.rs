@@ -0,0 +1,234 @@
+use egui::{Align2, Color32, Sense, Stroke, TextStyle, Ui};
+
+use crate::game::ability::Targeting;
+use crate::game::combat::PlayerIntent;
+use crate::game::content::Item;
+use crate::game::equipment::EquipSlot;
+use crate::game::event::FailReason;
+use crate::game::explore::Mode;
+use crate::game::inventory::{InventoryError, ItemStack};
+use crate::game::world::{Combatant, CombatantId, World};
+
+use super::charsheet;
+use super::eventlog::{self, ITEM_COLOR};
+
+const COLUMNS: usize = 4;
+const SLOT_SIZE: f32 = 56.0;
+const SLOT_COLOR: Color32 = Color32::from_rgb(40, 40, 40);
+const SELECTED_COLOR: Color32 = Color32::from_rgb(70, 70, 100);
+
+/// Free inventory changes, applied to the player by the main loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InventoryCommand {
+    Move { from: usize, to: usize },
+    Equip { slot: usize },
+    Unequip { slot: EquipSlot },
+}
+
+/// Open, selection and drag state of the inventory window.
+pub struct InventoryView {
+    pub open: bool,
+    selected: Option<usize>,
+    /// The slot being dragged, while the mouse is down.
+    dragging: Option<usize>,
+    pub commands: Vec<InventoryCommand>,
+    /// Why the last command did nothing, set by the main loop.
+    pub error: Option<InventoryError>,
+}
+
+impl InventoryView {
+    pub fn new() -> Self {
+        InventoryView { open: false, selected: None, dragging: None, commands: Vec::new(), error: None }
+    }
+}
+
+/// Slots of the player's inventory, drag one onto another to move it.
+/// Using an item is an action and spends the player's turn.
+pub fn draw_inventory(
+    ui: &mut Ui,
+    w: &World,
+    player: &Combatant,
+    enemy: Option<CombatantId>,
+    view: &mut InventoryView,
+    intents: &mut Vec<PlayerIntent>,
+) {
+    let inventory = &player.inventory;
+    let exploring = w.mode == Mode::Exploring;
+    draw_equipment(ui, player, exploring, view);
+    ui.separator();
+    ui.label(format!("Weight {}/{}", inventory.weight(), inventory.max_weight));
+    if let Some(error) = view.error {
+        ui.colored_label(Color32::RED, match error {
+            InventoryError::Full => "No room in the inventory",
+            InventoryError::TooHeavy => "Too heavy to carry",
+            InventoryError::NotGear => "That can't be worn",
+            InventoryError::InCombat => "Gear can't be changed mid-fight",
+        });
+    }
+
+    let mut rects = Vec::new();
+    for row in inventory.slots().chunks(COLUMNS) {
+        ui.horizontal(|ui| {
+            for stack in row {
+                let slot = rects.len();
+                let (rect, response) = ui.allocate_exact_size(egui::vec2(SLOT_SIZE, SLOT_SIZE), Sense::click_and_drag());
+                rects.push(rect);
+                let fill = if view.selected == Some(slot) { SELECTED_COLOR } else { SLOT_COLOR };
+                let painter = ui.painter();
+                painter.rect_filled(rect, 3.0, fill);
+                painter.rect_stroke(rect, 3.0, Stroke::new(1.0, Color32::GRAY));
+                let stack = match stack {
+                    Some(stack) => stack,
+                    None => continue,
+                };
+                painter.text(rect.center(), Align2::CENTER_CENTER, short_name(&stack.item), TextStyle::Small, ITEM_COLOR);
+                if stack.count > 1 {
+                    let corner = rect.right_bottom() - egui::vec2(4.0, 2.0);
+                    painter.text(corner, Align2::RIGHT_BOTTOM, stack.count.to_string(), TextStyle::Small, Color32::WHITE);
+                }
+                if response.clicked {
+                    view.selected = Some(slot);
+                }
+                if response.active && view.dragging.is_none() && ui.input().mouse.delta != egui::Vec2::zero() {
+                    view.dragging = Some(slot);
+                }
+                if view.dragging.is_none() {
+                    response.on_hover_text(item_tooltip(w, stack));
+                }
+            }
+        });
+    }
+
+    if let Some(from) = view.dragging {
+        let mouse = &ui.input().mouse;
+        match (mouse.down, mouse.pos) {
+            (true, Some(pos)) => {
+                let name = inventory.get(from).map(|s| short_name(&s.item)).unwrap_or_default();
+                ui.painter().text(pos, Align2::CENTER_CENTER, name, TextStyle::Small, ITEM_COLOR);
+            }
+            (_, pos) => {
+                if let Some(to) = pos.and_then(|pos| rects.iter().position(|r| r.contains(pos))) {
+                    view.commands.push(InventoryCommand::Move { from, to });
+                    view.selected = Some(to);
+                }
+                view.dragging = None;
+            }
+        }
+    }
+
+    ui.separator();
+    let selected = match view.selected.and_then(|slot| inventory.get(slot).map(|s| (slot, s))) {
+        Some(selected) => selected,
+        None => {
+            ui.label("Select an item, drag to move it.");
+            return;
+        }
+    };
+    let (slot, stack) = selected;
+    ui.colored_label(ITEM_COLOR, &stack.item);
+    ui.label(item_tooltip(w, stack));
+    let is_gear = w.item(&stack.item).is_some_and(|i| i.gear.is_some());
+    if is_gear && gear_button(ui, egui::Button::new("Equip"), exploring).clicked {
+        view.commands.push(InventoryCommand::Equip { slot });
+    }
+    let (intent, why_not) = use_intent(w, player, enemy, slot, stack);
+    let my_turn = w.combat.awaiting_input() == Some(player.id);
+    let why_not = if my_turn { why_not } else { Some("not your turn".to_string()) };
+    let mut response = ui.add(egui::Button::new("Use").enabled(why_not.is_none()));
+    if let Some(why_not) = why_not {
+        response = response.on_hover_text(why_not);
+    }
+    if response.clicked {
+        intents.extend(intent);
+    }
+}
+
+/// Worn gear, one row per slot. It can only be taken off while `exploring`.
+fn draw_equipment(ui: &mut Ui, player: &Combatant, exploring: bool, view: &mut InventoryView) {
+    egui::Grid::new("equipment").show(ui, |ui| {
+        for slot in EquipSlot::ALL.iter() {
+            ui.label(slot.name());
+            match player.equipment.get(*slot) {
+                Some(item) => {
+                    ui.colored_label(ITEM_COLOR, &item.name).on_hover_text(gear_lines(item).join("\n"));
+                    if gear_button(ui, egui::Button::new("Unequip").small(), exploring).clicked {
+                        view.commands.push(InventoryCommand::Unequip { slot: *slot });
+                    }
+                }
+                None => {
+                    ui.label("-");
+                }
+            }
+            ui.end_row();
+        }
+    });
+}
+
+/// A button that changes gear, disabled with a hint unless `exploring`.
+fn gear_button(ui: &mut Ui, button: egui::Button, exploring: bool) -> egui::Response {
+    let response = ui.add(button.enabled(exploring));
+    if exploring {
+        response
+    } else {
+        response.on_hover_text("Gear can only be changed between fights")
+    }
+}
+
+/// What using the item in `slot` would ask for, or why it can't be used.
+fn use_intent(
+    w: &World,
+    player: &Combatant,
+    enemy: Option<CombatantId>,
+    slot: usize,
+    stack: &ItemStack,
+) -> (Option<PlayerIntent>, Option<String>) {
+    let ability = w.item(&stack.item).and_then(|i| i.ability.as_ref()).and_then(|a| w.ability(a));
+    let target = match ability.map(|a| a.targeting) {
+        Some(Targeting::Enemy) | Some(Targeting::AllEnemies) => enemy,
+        Some(Targeting::User) | Some(Targeting::Ally) => Some(player.id),
+        None => return (None, Some(eventlog::fail_reason(FailReason::NotUsable))),
+    };
+    match target {
+        Some(target) => (Some(PlayerIntent::UseItem { slot, target }), None),
+        None => (None, Some(eventlog::fail_reason(FailReason::InvalidTarget))),
+    }
+}
+
+fn item_tooltip(w: &World, stack: &ItemStack) -> String {
+    let mut lines = vec![stack.item.clone()];
+    if let Some(item) = w.item(&stack.item) {
+        if !item.description.is_empty() {
+            lines.push(item.description.clone());
+        }
+        if let Some(ability) = &item.ability {
+            lines.push(format!("Use: {}", ability));
+        }
+        lines.extend(gear_lines(item));
+        lines.push(format!("Weight {} ({} in stack, up to {})", stack.weight, stack.count, item.stack));
+    }
+    lines.join("\n")
+}
+
+/// Where gear goes and what it changes, nothing for other items.
+fn gear_lines(item: &Item) -> Vec<String> {
+    let gear = match item.gear {
+        Some(gear) => gear,
+        None => return Vec::new(),
+    };
+    let mut lines = vec![match gear.damage_type {
+        Some(damage_type) => format!("{:?}, attacks deal {}", gear.slot, damage_type),
+        None => format!("{:?}", gear.slot),
+    }];
+    lines.extend(charsheet::modifier_lines(&gear.modifiers));
+    lines
+}
+
+/// Fits an item name in a slot.
+fn short_name(name: &str) -> String {
+    const MAX: usize = 9;
+    if name.chars().count() <= MAX {
+        name.to_string()
+    } else {
+        format!("{}.", name.chars().take(MAX - 1).collect::<String>())
+    }
+}
@@ -0,0 +1,91 @@
+use egui::{Align, Layout, Rect, Ui};
+
+/// Width of the character sheet column, as a fraction of the screen.
+pub const SHEET_WIDTH: f32 = 0.19;
+
+const REGION_MARGIN: f32 = 4.0;
+
+/// Regions of the combat screen right of the character sheet, laid out like
+/// etc/ui_example.jpg. Everything is a fraction of the area it is split from,
+/// so it follows the screen size and DPI scale.
+pub struct CombatLayout {
+    pub portrait: Rect,
+    pub player_bars: Rect,
+    pub turn_order: Rect,
+    pub scene: Rect,
+    pub log: Rect,
+    pub map: Rect,
+    pub enemy_art: Rect,
+    pub enemy_bars: Rect,
+    pub actions: Rect,
+}
+
+/// The part of `area` between the given fractions of its width and height.
+fn part(area: Rect, x: (f32, f32), y: (f32, f32)) -> Rect {
+    Rect::from_min_max(
+        area.min + egui::vec2(area.width() * x.0, area.height() * y.0),
+        area.min + egui::vec2(area.width() * x.1, area.height() * y.1),
+    )
+}
+
+impl CombatLayout {
+    pub fn split(area: Rect) -> Self {
+        // Column and row boundaries, in fractions of `area`.
+        let (side, center, right) = ((0.0, 0.19), (0.19, 0.82), (0.82, 1.0));
+        let actions_top = 0.8;
+        CombatLayout {
+            portrait: part(area, side, (0.0, 0.42)),
+            player_bars: part(area, side, (0.42, 0.52)),
+            turn_order: part(area, side, (0.52, actions_top)),
+            scene: part(area, center, (0.0, 0.33)),
+            log: part(area, center, (0.33, actions_top)),
+            map: part(area, right, (0.0, 0.33)),
+            enemy_art: part(area, right, (0.33, 0.68)),
+            enemy_bars: part(area, right, (0.68, actions_top)),
+            actions: part(area, (side.0, center.1), (actions_top, 1.0)),
+        }
+    }
+}
+
+/// Regions of the exploration screen right of the character sheet:
+/// a big map, with the log and the movement controls beside it.
+pub struct ExploreLayout {
+    pub map: Rect,
+    pub log: Rect,
+    pub controls: Rect,
+}
+
+impl ExploreLayout {
+    pub fn split(area: Rect) -> Self {
+        let (left, right) = ((0.0, 0.72), (0.72, 1.0));
+        ExploreLayout {
+            map: part(area, left, (0.0, 1.0)),
+            log: part(area, right, (0.0, 0.7)),
+            controls: part(area, right, (0.7, 1.0)),
+        }
+    }
+}
+
+/// Splits `area` into a grid of equally sized cells, row by row.
+pub fn grid_cells(area: Rect, columns: usize, rows: usize) -> Vec<Rect> {
+    let mut cells = Vec::with_capacity(columns * rows);
+    for row in 0..rows {
+        for column in 0..columns {
+            let x = (column as f32 / columns as f32, (column + 1) as f32 / columns as f32);
+            let y = (row as f32 / rows as f32, (row + 1) as f32 / rows as f32);
+            cells.push(part(area, x, y));
+        }
+    }
+    cells
+}
+
+/// Draws a framed region at `rect` and lets `add_contents` fill it.
+/// Whatever does not fit is clipped.
+pub fn region<R>(ui: &mut Ui, rect: Rect, add_contents: impl FnOnce(&mut Ui) -> R) -> R {
+    let stroke = ui.style().visuals.widgets.noninteractive.bg_stroke;
+    ui.painter().rect_stroke(rect, 0.0, stroke);
+    let inner = rect.shrink(REGION_MARGIN);
+    let mut child = ui.child_ui(inner, Layout::top_down(Align::Min));
+    child.set_clip_rect(inner.intersect(ui.clip_rect()));
+    add_contents(&mut child)
+}
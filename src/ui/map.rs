@@ -0,0 +1,41 @@
+use egui::{Color32, Rect, Ui};
+
+use crate::game::dungeon::{Dungeon, Pos, Tile};
+
+use super::eventlog::{ENEMY_COLOR, ITEM_COLOR, YOU_COLOR};
+
+const FLOOR_COLOR: Color32 = Color32::from_rgb(90, 90, 90);
+const DOOR_COLOR: Color32 = Color32::from_rgb(140, 100, 50);
+const STAIRS_COLOR: Color32 = Color32::from_rgb(230, 230, 230);
+
+/// The whole dungeon level, scaled to fit the space left in `ui`, with the player at `player`.
+pub fn draw_map(ui: &mut Ui, dungeon: &Dungeon, player: Pos) {
+    let area = ui.available_rect_before_wrap_finite();
+    if dungeon.width == 0 || dungeon.height == 0 {
+        return;
+    }
+    let cell = (area.width() / dungeon.width as f32).min(area.height() / dungeon.height as f32);
+    let rect_of = |pos: Pos| {
+        Rect::from_min_size(area.min + egui::vec2(pos.x as f32 * cell, pos.y as f32 * cell), egui::vec2(cell, cell))
+    };
+    let painter = ui.painter();
+    for y in 0..dungeon.height {
+        for x in 0..dungeon.width {
+            let pos = Pos::new(x, y);
+            let color = match dungeon.tile(pos) {
+                Tile::Wall => continue,
+                Tile::Floor => FLOOR_COLOR,
+                Tile::Door => DOOR_COLOR,
+                Tile::StairsUp | Tile::StairsDown => STAIRS_COLOR,
+            };
+            painter.rect_filled(rect_of(pos), 0.0, color);
+        }
+    }
+    for encounter in dungeon.encounters.iter().filter(|e| !e.cleared) {
+        painter.circle_filled(rect_of(encounter.pos).center(), cell * 0.4, ENEMY_COLOR);
+    }
+    for treasure in dungeon.treasures.iter().filter(|t| !t.taken) {
+        painter.circle_filled(rect_of(treasure.pos).center(), cell * 0.3, ITEM_COLOR);
+    }
+    painter.circle_filled(rect_of(player).center(), cell * 0.45, YOU_COLOR);
+}
@@ -1,3 +1,10 @@
 pub use self::draw::*;
 
-pub mod draw;
\ No newline at end of file
+pub mod bars;
+pub mod charsheet;
+pub mod creation;
+pub mod draw;
+pub mod eventlog;
+pub mod inventory;
+pub mod layout;
+pub mod map;
\ No newline at end of file